@@ -0,0 +1,251 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+declare_id!("4JKp3HmKQubZnKV4QYKEdHeTiKvVZUGTfD2jXeeY1N4v");
+
+/// Fixed-rate swap used to exercise the vault's compound_premium CPI locally.
+/// `swap` follows the vault swap interface: source, destination, authority,
+/// source mint, destination mint, source and destination token programs, then
+/// program-specific accounts. Either mint may be SPL Token or Token-2022.
+#[program]
+pub mod mock_swap {
+    use super::*;
+
+    /// Create a pool converting input_mint to output_mint at
+    /// rate_numerator / rate_denominator (base units). The output reserve
+    /// must be funded before swapping.
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        rate_numerator: u64,
+        rate_denominator: u64,
+    ) -> Result<()> {
+        require!(
+            rate_numerator > 0 && rate_denominator > 0,
+            MockSwapError::InvalidRate
+        );
+
+        let pool = &mut ctx.accounts.pool;
+        pool.input_mint = ctx.accounts.input_mint.key();
+        pool.output_mint = ctx.accounts.output_mint.key();
+        pool.input_reserve = ctx.accounts.input_reserve.key();
+        pool.output_reserve = ctx.accounts.output_reserve.key();
+        pool.rate_numerator = rate_numerator;
+        pool.rate_denominator = rate_denominator;
+        pool.bump = ctx.bumps.pool;
+
+        Ok(())
+    }
+
+    /// Swap amount_in of input_mint for output_mint at the pool rate
+    pub fn swap(ctx: Context<Swap>, amount_in: u64, min_amount_out: u64) -> Result<()> {
+        require!(amount_in > 0, MockSwapError::ZeroAmount);
+
+        let pool = &ctx.accounts.pool;
+        let amount_out = (amount_in as u128)
+            .checked_mul(pool.rate_numerator as u128)
+            .ok_or(MockSwapError::Overflow)?
+            .checked_div(pool.rate_denominator as u128)
+            .ok_or(MockSwapError::Overflow)? as u64;
+        require!(
+            amount_out >= min_amount_out,
+            MockSwapError::SlippageExceeded
+        );
+        require!(
+            ctx.accounts.output_reserve.amount >= amount_out,
+            MockSwapError::InsufficientLiquidity
+        );
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.input_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.source.to_account_info(),
+                    mint: ctx.accounts.input_mint.to_account_info(),
+                    to: ctx.accounts.input_reserve.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount_in,
+            ctx.accounts.input_mint.decimals,
+        )?;
+
+        let input_mint = pool.input_mint;
+        let output_mint = pool.output_mint;
+        let seeds = &[
+            b"pool",
+            input_mint.as_ref(),
+            output_mint.as_ref(),
+            &[pool.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.output_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.output_reserve.to_account_info(),
+                    mint: ctx.accounts.output_mint.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_out,
+            ctx.accounts.output_mint.decimals,
+        )?;
+
+        emit!(SwapEvent {
+            pool: ctx.accounts.pool.key(),
+            amount_in,
+            amount_out,
+        });
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Account Structures
+// ============================================================================
+
+#[account]
+pub struct Pool {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub input_reserve: Pubkey,
+    pub output_reserve: Pubkey,
+    pub rate_numerator: u64,
+    pub rate_denominator: u64,
+    pub bump: u8,
+}
+
+// ============================================================================
+// Contexts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        // 8 (discriminator) + 32*4 (mints, reserves) + 8 + 8 (rate) + 1 (bump)
+        space = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 1,
+        seeds = [b"pool", input_mint.key().as_ref(), output_mint.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mint::token_program = input_token_program)]
+    pub input_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = output_token_program)]
+    pub output_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = input_mint,
+        token::authority = pool,
+        token::token_program = input_token_program,
+        seeds = [b"reserve", pool.key().as_ref(), input_mint.key().as_ref()],
+        bump
+    )]
+    pub input_reserve: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = output_mint,
+        token::authority = pool,
+        token::token_program = output_token_program,
+        seeds = [b"reserve", pool.key().as_ref(), output_mint.key().as_ref()],
+        bump
+    )]
+    pub output_reserve: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        mut,
+        token::mint = pool.input_mint
+    )]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = pool.output_mint
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        address = pool.input_mint,
+        mint::token_program = input_token_program
+    )]
+    pub input_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.output_mint,
+        mint::token_program = output_token_program
+    )]
+    pub output_mint: InterfaceAccount<'info, Mint>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    #[account(
+        seeds = [b"pool", pool.input_mint.as_ref(), pool.output_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        address = pool.input_reserve
+    )]
+    pub input_reserve: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.output_reserve
+    )]
+    pub output_reserve: InterfaceAccount<'info, TokenAccount>,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[error_code]
+pub enum MockSwapError {
+    #[msg("Rate must be non-zero")]
+    InvalidRate,
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Output below minimum")]
+    SlippageExceeded,
+    #[msg("Insufficient output reserve")]
+    InsufficientLiquidity,
+}
@@ -1,6 +1,25 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        scaled_ui_amount::ScaledUiAmountConfig, transfer_hook::TransferHookAccount,
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+};
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{
+    self, Approve, Burn, CloseAccount, Mint, MintTo, Revoke, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+use solana_instructions_sysvar as ix_sysvar;
+use solana_sdk_ids::{ed25519_program, sysvar};
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 
 declare_id!("A4jgqct3bwTwRmHECHdPpbH3a8ksaVb7rny9pMUGFo94");
 
@@ -10,6 +29,53 @@ const METADATA_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
     26, 160, 253, 181, 73, 182, 209, 188, 3, 248, 41, 70,
 ]);
 
+// Pyth Solana Receiver Program ID (owner of PriceUpdateV2 accounts)
+const PYTH_RECEIVER_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    12, 183, 250, 187, 82, 247, 166, 72, 187, 91, 49, 125, 154, 1, 139, 144, 87, 203, 2, 71, 116,
+    250, 254, 1, 230, 196, 223, 152, 204, 56, 88, 129,
+]);
+
+// Anchor discriminator of the Pyth PriceUpdateV2 account
+const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+// Fixed-point scale for price-per-share values
+const PRICE_PER_SHARE_SCALE: u128 = 1_000_000_000;
+
+// Time a market maker has to exercise a physically-settled ITM position
+const EXERCISE_WINDOW: i64 = 86400; // 24 hours
+
+// Time a market maker has to pay premium owed for a recorded fill before
+// its collateral can be slashed
+const PREMIUM_GRACE_WINDOW: i64 = 3600; // 1 hour
+
+// Collateral a market maker must post against the strike value of its open
+// positions, on top of any unpaid premium
+const MAKER_COLLATERAL_BPS: u16 = 1000; // 10%
+
+// Instruction discriminator of the swap interface used by compound_premium:
+// swap(amount_in: u64, min_amount_out: u64) with accounts
+// [source, destination, authority (signer), source mint, destination mint,
+//  source token program, destination token program, ...remaining]
+const SWAP_IX_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+// Governance timelock applied to every queued admin action
+const DEFAULT_TIMELOCK_DELAY: i64 = 86400; // 24 hours
+const MIN_TIMELOCK_DELAY: i64 = 3600; // 1 hour
+const MAX_TIMELOCK_DELAY: i64 = 30 * 86400; // 30 days
+
+// Vault account layout version written by initialize_vault and migrate_vault
+const CURRENT_VAULT_VERSION: u8 = 1;
+// Size of every version 0 Vault account (LegacyVaultV0 with a 64-byte asset_id)
+const VAULT_V0_LEN: usize = 406;
+// Offset of Vault::version: after the discriminator and authority
+const VAULT_VERSION_OFFSET: usize = 40;
+
+// Fee limits
+const SECONDS_PER_YEAR: u128 = 365 * 86400;
+const MAX_MANAGEMENT_FEE_BPS: u16 = 500; // 5% per year
+const MAX_PERFORMANCE_FEE_BPS: u16 = 3000; // 30% of premium
+const MAX_FLOW_FEE_BPS: u16 = 100; // 1% on deposit / withdrawal
+
 #[program]
 pub mod vault {
     use super::*;
@@ -21,6 +87,7 @@ pub mod vault {
         asset_id: String,
         utilization_cap_bps: u16,
         min_epoch_duration: i64,
+        strategy: Strategy,
     ) -> Result<()> {
         // SECURITY: Validate underlying mint is not suspicious
         // Check for freeze authority (suspicious for DeFi)
@@ -31,6 +98,10 @@ pub mod vault {
             // return err!(VaultError::SuspiciousMint);
         }
 
+        // SECURITY: Token-2022 mints may only carry extensions the vault can custody
+        validate_mint_extensions(&ctx.accounts.underlying_mint)?;
+        validate_mint_extensions(&ctx.accounts.premium_mint)?;
+
         // SECURITY: Validate premium mint (USDC)
         // Premium mint should be a known stablecoin
         let premium_mint_key = ctx.accounts.premium_mint.key();
@@ -39,13 +110,16 @@ pub mod vault {
         msg!("Premium mint: {}", premium_mint_key);
         let vault = &mut ctx.accounts.vault;
         vault.authority = ctx.accounts.authority.key();
+        vault.keeper = ctx.accounts.authority.key();
+        vault.guardian = ctx.accounts.authority.key();
+        vault.settlement_operator = ctx.accounts.authority.key();
+        vault.timelock_delay = DEFAULT_TIMELOCK_DELAY;
         vault.asset_id = asset_id;
         vault.underlying_mint = ctx.accounts.underlying_mint.key();
         vault.share_mint = ctx.accounts.share_mint.key();
         vault.vault_token_account = ctx.accounts.vault_token_account.key();
         vault.premium_mint = ctx.accounts.premium_mint.key();
         vault.premium_token_account = ctx.accounts.premium_token_account.key();
-        vault.share_escrow = ctx.accounts.share_escrow.key();
         vault.total_assets = 0;
         vault.total_shares = 0;
         vault.epoch = 0;
@@ -57,11 +131,22 @@ pub mod vault {
         vault.epoch_premium_earned = 0;
         vault.epoch_premium_per_token_bps = 0;
         vault.is_paused = false;
+        vault.strategy = strategy;
+        vault.underlying_decimals = ctx.accounts.underlying_mint.decimals;
+        vault.underlying_token_program = ctx.accounts.underlying_token_program.key();
+        vault.underlying_multiplier =
+            mint_ui_multiplier(&ctx.accounts.underlying_mint, Clock::get()?.unix_timestamp)?;
+        vault.premium_token_program = ctx.accounts.premium_token_program.key();
+        vault.fee_recipient = ctx.accounts.authority.key();
+        vault.version = CURRENT_VAULT_VERSION;
         vault.bump = ctx.bumps.vault;
+
+        init_share_hook_metas(&ctx.accounts.extra_account_meta_list, &vault.key())?;
         Ok(())
     }
 
-    /// Deposit underlying tokens and receive vault shares
+    /// Deposit collateral and receive vault shares
+    /// Covered-call vaults take the underlying, cash-secured put vaults the premium mint
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         require!(amount > 0, VaultError::ZeroAmount);
 
@@ -69,6 +154,38 @@ pub mod vault {
 
         // SECURITY: Check if vault is paused
         require!(!vault.is_paused, VaultError::VaultPaused);
+        vault.check_attestation(
+            ctx.accounts.attestation.as_deref(),
+            Clock::get()?.unix_timestamp,
+        )?;
+        // Queued vaults only admit capital at epoch boundaries (request_deposit)
+        require!(
+            !vault.deposit_queue_enabled,
+            VaultError::DepositQueueEnabled
+        );
+
+        // Transfer collateral from user to vault
+        // Shares are priced on the amount received (net of Token-2022 transfer fees)
+        let amount = transfer_in(
+            &ctx.accounts.collateral_token_program,
+            ctx.accounts.user_token_account.to_account_info(),
+            &mut ctx.accounts.vault_token_account,
+            &ctx.accounts.collateral_mint,
+            ctx.accounts.user.to_account_info(),
+            amount,
+            &[],
+        )?;
+        require!(amount > 0, VaultError::ZeroAmount);
+
+        // Guarded launch: TVL and per-wallet caps
+        let user_deposits = &mut ctx.accounts.user_deposits;
+        vault.check_deposit_caps(user_deposits.deposited, amount)?;
+        user_deposits.record_deposit(
+            vault.key(),
+            ctx.accounts.user.key(),
+            ctx.bumps.user_deposits,
+            amount,
+        )?;
 
         // Calculate shares to mint
         // effective_total_shares = total_shares + virtual_offset
@@ -100,25 +217,19 @@ pub mod vault {
 
         require!(shares_to_mint > 0, VaultError::ZeroShares);
 
-        // Transfer underlying tokens from user to vault
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.user_token_account.to_account_info(),
-                    to: ctx.accounts.vault_token_account.to_account_info(),
-                    authority: ctx.accounts.user.to_account_info(),
-                },
-            ),
-            amount,
-        )?;
+        // Deposit fee is taken in shares and minted to the fee recipient
+        let fee_shares = bps_of(shares_to_mint, vault.deposit_fee_bps)?;
+        let user_shares = shares_to_mint
+            .checked_sub(fee_shares)
+            .ok_or(VaultError::Overflow)?;
+        require!(user_shares > 0, VaultError::ZeroShares);
 
         // Mint vault shares to user
         let asset_id = vault.asset_id.as_bytes();
         let seeds = &[b"vault", asset_id, &[vault.bump]];
         let signer_seeds = &[&seeds[..]];
 
-        token::mint_to(
+        token_interface::mint_to(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 MintTo {
@@ -128,9 +239,35 @@ pub mod vault {
                 },
                 signer_seeds,
             ),
-            shares_to_mint,
+            user_shares,
         )?;
 
+        if fee_shares > 0 {
+            let fee_share_account = ctx
+                .accounts
+                .fee_share_account
+                .as_ref()
+                .ok_or(VaultError::FeeAccountMissing)?;
+            sync_fee_checkpoint(
+                ctx.accounts.fee_premium_checkpoint.as_mut(),
+                vault,
+                fee_share_account,
+                fee_shares,
+            )?;
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.share_mint.to_account_info(),
+                        to: fee_share_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee_shares,
+            )?;
+        }
+
         // Update vault state
         vault.total_assets = vault
             .total_assets
@@ -149,11 +286,25 @@ pub mod vault {
             msg!("Auto-started epoch 1 on first deposit");
         }
 
+        sync_premium_checkpoint(
+            &mut ctx.accounts.premium_checkpoint,
+            vault,
+            ctx.accounts.user_share_account.key(),
+            ctx.accounts.user_share_account.amount,
+            ctx.accounts
+                .user_share_account
+                .amount
+                .checked_add(user_shares)
+                .ok_or(VaultError::Overflow)?,
+            ctx.bumps.premium_checkpoint,
+        )?;
+
         emit!(DepositEvent {
             vault: vault.key(),
             user: ctx.accounts.user.key(),
             amount,
-            shares_minted: shares_to_mint,
+            shares_minted: user_shares,
+            fee_shares,
             epoch: vault.epoch,
         });
 
@@ -176,13 +327,15 @@ pub mod vault {
             VaultError::InsufficientShares
         );
 
-        // Transfer shares to vault escrow
-        token::transfer(
+        // Burn the shares now; they stay in total_shares until the roll they
+        // exit at. Shares are not escrowed: the vault cannot transfer them
+        // itself, as the share mint's transfer hook would re-enter the program
+        token_interface::burn(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
                     from: ctx.accounts.user_share_account.to_account_info(),
-                    to: ctx.accounts.share_escrow.to_account_info(),
                     authority: ctx.accounts.user.to_account_info(),
                 },
             ),
@@ -194,11 +347,21 @@ pub mod vault {
         withdrawal.shares = shares;
         withdrawal.request_epoch = vault.epoch;
         withdrawal.processed = false;
-
-        vault.pending_withdrawals = vault
-            .pending_withdrawals
-            .checked_add(shares)
-            .ok_or(VaultError::Overflow)?;
+        withdrawal.premium_index_at_request = vault.queue_withdrawal(shares)?;
+
+        // Burned shares stop accruing; premium credited from here is paid on processing
+        sync_premium_checkpoint(
+            &mut ctx.accounts.premium_checkpoint,
+            vault,
+            ctx.accounts.user_share_account.key(),
+            ctx.accounts.user_share_account.amount,
+            ctx.accounts
+                .user_share_account
+                .amount
+                .checked_sub(shares)
+                .ok_or(VaultError::InsufficientShares)?,
+            ctx.bumps.premium_checkpoint,
+        )?;
 
         emit!(WithdrawalRequestedEvent {
             vault: vault.key(),
@@ -210,1183 +373,6072 @@ pub mod vault {
         Ok(())
     }
 
-    /// Process withdrawal after epoch settles
-    /// SECURITY FIX H-3: Added min_expected_amount for slippage protection
-    /// SECURITY FIX M-3: Blocked when vault is paused
-    pub fn process_withdrawal(
-        ctx: Context<ProcessWithdrawal>,
-        min_expected_amount: u64,
-    ) -> Result<()> {
-        let withdrawal = &mut ctx.accounts.withdrawal_request;
+    /// Cancel a pending withdrawal within the epoch it was requested
+    /// Re-mints the burned shares and closes the request (rent refunded to user)
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        let withdrawal = &ctx.accounts.withdrawal_request;
         let vault = &mut ctx.accounts.vault;
 
-        // SECURITY FIX M-3: Block withdrawals when paused (emergency protection)
-        require!(!vault.is_paused, VaultError::VaultPaused);
-
         require!(!withdrawal.processed, VaultError::AlreadyProcessed);
+        // Once the epoch rolls the shares have exited and can only be processed
         require!(
-            vault.epoch > withdrawal.request_epoch,
-            VaultError::EpochNotSettled
+            vault.epoch == withdrawal.request_epoch,
+            VaultError::WithdrawalAlreadyExited
         );
 
         let shares = withdrawal.shares;
 
-        // Calculate underlying amount to return using effective shares
-        // effective_shares = total_shares + virtual_offset
-        let effective_shares = vault
-            .total_shares
-            .checked_add(vault.virtual_offset)
-            .ok_or(VaultError::Overflow)?;
-
-        // SECURITY FIX M-2: Explicit division by zero check
-        require!(effective_shares > 0, VaultError::DivisionByZero);
-
-        let amount = (shares as u128)
-            .checked_mul(vault.total_assets as u128)
-            .ok_or(VaultError::Overflow)?
-            .checked_div(effective_shares as u128)
-            .ok_or(VaultError::Overflow)? as u64;
-
-        // SECURITY FIX H-3: Slippage protection - user specifies minimum acceptable amount
-        require!(amount >= min_expected_amount, VaultError::SlippageExceeded);
-
-        // SECURITY FIX H-1: Verify vault has sufficient assets
-        require!(
-            vault.total_assets >= amount,
-            VaultError::InsufficientVaultBalance
-        );
-
-        // Burn user's shares
         let asset_id = vault.asset_id.as_bytes();
         let seeds = &[b"vault", asset_id, &[vault.bump]];
         let signer_seeds = &[&seeds[..]];
 
-        token::burn(
+        token_interface::mint_to(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                Burn {
+                MintTo {
                     mint: ctx.accounts.share_mint.to_account_info(),
-                    from: ctx.accounts.share_escrow.to_account_info(),
-                    authority: vault.to_account_info(), // Vault auth
+                    to: ctx.accounts.user_share_account.to_account_info(),
+                    authority: vault.to_account_info(),
                 },
                 signer_seeds,
             ),
             shares,
         )?;
 
-        // Transfer underlying tokens back to user
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
-                    to: ctx.accounts.user_token_account.to_account_info(),
-                    authority: vault.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            amount,
+        vault.unqueue_withdrawal(shares, withdrawal.premium_index_at_request)?;
+
+        // The burned shares still earned premium credited since the request
+        sync_premium_checkpoint(
+            &mut ctx.accounts.premium_checkpoint,
+            vault,
+            ctx.accounts.user_share_account.key(),
+            ctx.accounts.user_share_account.amount,
+            ctx.accounts
+                .user_share_account
+                .amount
+                .checked_add(shares)
+                .ok_or(VaultError::Overflow)?,
+            ctx.bumps.premium_checkpoint,
+        )?;
+        ctx.accounts.premium_checkpoint.accrue_since(
+            vault.premium_per_share_index,
+            withdrawal.premium_index_at_request,
+            shares,
         )?;
 
-        // Calculate and claim proportional USDC premiums
-        // Claimable = (shares / effective_shares) * premium_balance_usdc
-        let premium_balance = vault.premium_balance_usdc;
-        if premium_balance > 0 {
-            let user_premium_share = (shares as u128)
-                .checked_mul(premium_balance as u128)
-                .ok_or(VaultError::Overflow)?
-                .checked_div(effective_shares as u128)
-                .ok_or(VaultError::Overflow)? as u64;
-
-            if user_premium_share > 0 {
-                // LONG-TERM FIX: Cap claim to actual token balance to handle state/balance drift
-                // This prevents "insufficient funds" errors if state drifts from actual balance
-                let actual_premium_balance = ctx.accounts.vault_premium_account.amount;
-                let capped_premium_share = user_premium_share.min(actual_premium_balance);
-
-                if capped_premium_share > 0 {
-                    // Transfer USDC to user
-                    token::transfer(
-                        CpiContext::new_with_signer(
-                            ctx.accounts.token_program.to_account_info(), // Standard token program for USDC
-                            Transfer {
-                                from: ctx.accounts.vault_premium_account.to_account_info(),
-                                to: ctx.accounts.user_premium_account.to_account_info(),
-                                authority: vault.to_account_info(),
-                            },
-                            signer_seeds,
-                        ),
-                        capped_premium_share,
-                    )?;
-
-                    // Deduct capped amount from vault state
-                    vault.premium_balance_usdc = vault
-                        .premium_balance_usdc
-                        .checked_sub(capped_premium_share)
-                        .ok_or(VaultError::Overflow)?;
-
-                    if capped_premium_share < user_premium_share {
-                        msg!(
-                            "WARNING: Premium capped from {} to {} due to balance drift",
-                            user_premium_share,
-                            capped_premium_share
-                        );
-                    }
-                    msg!("Withdrew premium share: {} USDC", capped_premium_share);
-                }
-            }
-        }
-
-        // Update vault state
-        vault.total_assets = vault
-            .total_assets
-            .checked_sub(amount)
-            .ok_or(VaultError::Overflow)?;
-        vault.total_shares = vault
-            .total_shares
-            .checked_sub(shares)
-            .ok_or(VaultError::Overflow)?;
-        vault.pending_withdrawals = vault
-            .pending_withdrawals
-            .checked_sub(shares)
-            .ok_or(VaultError::Overflow)?;
-
-        // Mark withdrawal as processed
-        withdrawal.processed = true;
-
-        emit!(WithdrawalProcessedEvent {
+        emit!(WithdrawalCancelledEvent {
             vault: vault.key(),
             user: ctx.accounts.user.key(),
             shares,
-            amount,
             epoch: vault.epoch,
         });
 
         Ok(())
     }
 
-    /// Advance epoch (called by keeper after settlement)
-    /// Premium earned is credited to total_assets, increasing share value
-    pub fn advance_epoch(ctx: Context<AdvanceEpoch>, premium_earned: u64) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        let clock = Clock::get()?;
+    /// Queue a deposit to be admitted at the next epoch roll
+    /// Collateral is held by the vault but earns nothing until advance_epoch
+    /// converts it to shares at that epoch's price-per-share
+    pub fn request_deposit(ctx: Context<RequestDeposit>, amount: u64) -> Result<()> {
+        require!(amount > 0, VaultError::ZeroAmount);
 
-        // 1. Timelock Check
-        require!(
-            clock.unix_timestamp >= vault.last_roll_timestamp + vault.min_epoch_duration,
-            VaultError::EpochTooShort
-        );
+        let vault = &mut ctx.accounts.vault;
+        let request = &mut ctx.accounts.deposit_request;
 
-        // 2. Unbounded Premium Check (TVL Cap)
-        // Premium shouldn't be > 50% of TVL in a single epoch (sanity check against infinite mint)
+        // SECURITY: Check if vault is paused
+        require!(!vault.is_paused, VaultError::VaultPaused);
+        vault.check_attestation(
+            ctx.accounts.attestation.as_deref(),
+            Clock::get()?.unix_timestamp,
+        )?;
         require!(
-            premium_earned <= vault.total_assets / 2,
-            VaultError::ExcessivePremium
+            vault.deposit_queue_enabled,
+            VaultError::DepositQueueDisabled
         );
 
-        // 3. Implied Yield Check
-        // If we have exposure, check that implied yield (premium / exposure) isn't insane (e.g., > 20% flat)
-        if vault.epoch_notional_exposed > 0 {
-            let yield_bps = (premium_earned as u128)
-                .checked_mul(10000)
-                .ok_or(VaultError::Overflow)?
-                .checked_div(vault.epoch_notional_exposed as u128)
-                .ok_or(VaultError::Overflow)?;
-
-            // 2000 bps = 20% return in a single epoch. That's extremely high.
-            require!(yield_bps <= 2000, VaultError::ExcessiveYield);
-        } else {
-            // No exposure => no premium expected (or very small)
-            require!(premium_earned == 0, VaultError::ExcessivePremium);
+        // A request from an earlier epoch must be claimed before queuing more
+        if request.amount > 0 {
+            require!(
+                request.request_epoch == vault.epoch,
+                VaultError::DepositClaimPending
+            );
         }
 
-        // Store epoch stats before resetting
-        let notional_exposed = vault.epoch_notional_exposed;
-        let avg_premium_bps = vault.epoch_premium_per_token_bps;
+        // Queue the amount received (net of Token-2022 transfer fees)
+        let amount = transfer_in(
+            &ctx.accounts.collateral_token_program,
+            ctx.accounts.user_token_account.to_account_info(),
+            &mut ctx.accounts.vault_token_account,
+            &ctx.accounts.collateral_mint,
+            ctx.accounts.user.to_account_info(),
+            amount,
+            &[],
+        )?;
+        require!(amount > 0, VaultError::ZeroAmount);
 
-        // Credit premium to USDC balance (separate from underlying TVL)
-        // This prevents the "flywheel" effect of synthetic token creation
-        vault.premium_balance_usdc = vault
-            .premium_balance_usdc
-            .checked_add(premium_earned)
-            .ok_or(VaultError::Overflow)?;
+        // Guarded launch: TVL and per-wallet caps
+        let user_deposits = &mut ctx.accounts.user_deposits;
+        vault.check_deposit_caps(user_deposits.deposited, amount)?;
+        user_deposits.record_deposit(
+            vault.key(),
+            ctx.accounts.user.key(),
+            ctx.bumps.user_deposits,
+            amount,
+        )?;
 
-        vault.epoch = vault.epoch.checked_add(1).ok_or(VaultError::Overflow)?;
-        vault.last_roll_timestamp = clock.unix_timestamp;
+        request.user = ctx.accounts.user.key();
+        request.vault = vault.key();
+        request.amount = request
+            .amount
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+        request.request_epoch = vault.epoch;
+        request.bump = ctx.bumps.deposit_request;
 
-        // Reset epoch tracking for new epoch
-        vault.epoch_notional_exposed = 0;
-        vault.epoch_premium_earned = 0;
-        vault.epoch_premium_per_token_bps = 0;
+        vault.pending_deposits = vault
+            .pending_deposits
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
 
-        emit!(EpochAdvancedEvent {
+        emit!(DepositRequestedEvent {
             vault: vault.key(),
-            new_epoch: vault.epoch,
-            premium_earned,
-            notional_exposed,
-            avg_premium_bps,
-            total_assets: vault.total_assets,
-            total_shares: vault.total_shares,
-            premium_balance_usdc: vault.premium_balance_usdc,
+            user: ctx.accounts.user.key(),
+            amount,
+            epoch: vault.epoch,
         });
 
         Ok(())
     }
 
-    /// Record notional exposure when an RFQ is filled (fractional options)
-    /// Premium is in premium_mint tokens (USDC)
-    pub fn record_notional_exposure(
-        ctx: Context<RecordNotionalExposure>,
-        notional_tokens: u64,
-        premium: u64,
-    ) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
+    /// Claim shares for a queued deposit once its epoch has rolled
+    /// Shares are priced from the EpochSnapshot written at admission
+    pub fn claim_deposit(ctx: Context<ClaimDeposit>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let request = &ctx.accounts.deposit_request;
+        let snapshot = &ctx.accounts.epoch_snapshot;
 
-        // Calculate max exposure: TVL * utilization_cap_bps / 10000
-        let max_exposure = (vault.total_assets as u128)
-            .checked_mul(vault.utilization_cap_bps as u128)
+        require!(
+            vault.epoch > request.request_epoch,
+            VaultError::EpochNotSettled
+        );
+        require!(snapshot.price_per_share > 0, VaultError::DivisionByZero);
+
+        let shares = (request.amount as u128)
+            .checked_mul(PRICE_PER_SHARE_SCALE)
             .ok_or(VaultError::Overflow)?
-            .checked_div(10000)
+            .checked_div(snapshot.price_per_share as u128)
             .ok_or(VaultError::Overflow)? as u64;
+        require!(shares > 0, VaultError::ZeroShares);
 
-        let new_exposure = vault
-            .epoch_notional_exposed
-            .checked_add(notional_tokens)
-            .ok_or(VaultError::Overflow)?;
+        // Deposit fee is taken in shares and minted to the fee recipient
+        let fee_shares = bps_of(shares, vault.deposit_fee_bps)?;
+        let user_shares = shares.checked_sub(fee_shares).ok_or(VaultError::Overflow)?;
 
-        require!(
-            new_exposure <= max_exposure,
-            VaultError::ExceedsUtilizationCap
-        );
+        let asset_id = vault.asset_id.as_bytes();
+        let seeds = &[b"vault", asset_id, &[vault.bump]];
+        let signer_seeds = &[&seeds[..]];
 
-        // Update epoch tracking
-        vault.epoch_notional_exposed = new_exposure;
-        vault.epoch_premium_earned = vault
-            .epoch_premium_earned
-            .checked_add(premium)
-            .ok_or(VaultError::Overflow)?;
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.user_share_account.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            user_shares,
+        )?;
 
-        // Calculate running average premium rate in basis points
-        if vault.epoch_notional_exposed > 0 {
-            vault.epoch_premium_per_token_bps = ((vault.epoch_premium_earned as u128)
-                .checked_mul(10000)
-                .ok_or(VaultError::Overflow)?
-                .checked_div(vault.epoch_notional_exposed as u128)
-                .ok_or(VaultError::Overflow)?)
-                as u32;
+        if fee_shares > 0 {
+            let fee_share_account = ctx
+                .accounts
+                .fee_share_account
+                .as_ref()
+                .ok_or(VaultError::FeeAccountMissing)?;
+            sync_fee_checkpoint(
+                ctx.accounts.fee_premium_checkpoint.as_mut(),
+                vault,
+                fee_share_account,
+                fee_shares,
+            )?;
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.share_mint.to_account_info(),
+                        to: fee_share_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee_shares,
+            )?;
         }
 
-        emit!(NotionalExposureEvent {
+        sync_premium_checkpoint(
+            &mut ctx.accounts.premium_checkpoint,
+            vault,
+            ctx.accounts.user_share_account.key(),
+            ctx.accounts.user_share_account.amount,
+            ctx.accounts
+                .user_share_account
+                .amount
+                .checked_add(user_shares)
+                .ok_or(VaultError::Overflow)?,
+            ctx.bumps.premium_checkpoint,
+        )?;
+
+        emit!(DepositClaimedEvent {
             vault: vault.key(),
-            epoch: vault.epoch,
-            notional_tokens,
-            premium,
-            total_notional_this_epoch: vault.epoch_notional_exposed,
-            total_premium_this_epoch: vault.epoch_premium_earned,
-            avg_premium_bps: vault.epoch_premium_per_token_bps,
+            user: ctx.accounts.user.key(),
+            amount: request.amount,
+            shares_minted: user_shares,
+            fee_shares,
+            epoch: snapshot.epoch,
         });
 
         Ok(())
     }
 
-    /// Collect premium from market maker (called during epoch roll)
-    /// Transfers USDC from payer to vault's premium account
-    /// SECURITY FIX M-1: Now requires authority signature to prevent front-running
-    /// SECURITY FIX M-1b: Now updates epoch_premium_earned to track state
-    pub fn collect_premium(ctx: Context<CollectPremium>, amount: u64) -> Result<()> {
-        require!(amount > 0, VaultError::ZeroAmount);
-
+    /// Claim USDC premium accrued on a share account without redeeming shares
+    /// Share transfers sync both accounts' checkpoints through the share
+    /// mint's transfer hook, so accrual follows the shares
+    pub fn claim_premium(ctx: Context<ClaimPremium>) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
 
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.payer_token_account.to_account_info(),
-                    to: ctx.accounts.vault_premium_account.to_account_info(),
-                    authority: ctx.accounts.payer.to_account_info(),
-                },
-            ),
-            amount,
+        // SECURITY: Check if vault is paused
+        require!(!vault.is_paused, VaultError::VaultPaused);
+
+        let balance = ctx.accounts.owner_share_account.amount;
+        sync_premium_checkpoint(
+            &mut ctx.accounts.premium_checkpoint,
+            vault,
+            ctx.accounts.owner_share_account.key(),
+            balance,
+            balance,
+            ctx.bumps.premium_checkpoint,
         )?;
 
-        // SECURITY FIX M-1b: Track collected premium in vault state
-        vault.epoch_premium_earned = vault
-            .epoch_premium_earned
-            .checked_add(amount)
+        // Cap to credited premium the account actually holds (put vaults keep
+        // collateral in the same account)
+        let amount = ctx
+            .accounts
+            .premium_checkpoint
+            .accrued
+            .min(vault.available_premium(ctx.accounts.vault_premium_account.amount));
+
+        if amount > 0 {
+            let asset_id = vault.asset_id.as_bytes();
+            let seeds = &[b"vault", asset_id, &[vault.bump]];
+            let signer_seeds = &[&seeds[..]];
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.premium_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault_premium_account.to_account_info(),
+                        mint: ctx.accounts.premium_mint.to_account_info(),
+                        to: ctx.accounts.owner_premium_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+                ctx.accounts.premium_mint.decimals,
+            )?;
+        }
+
+        let checkpoint = &mut ctx.accounts.premium_checkpoint;
+        checkpoint.accrued = checkpoint
+            .accrued
+            .checked_sub(amount)
+            .ok_or(VaultError::Overflow)?;
+        vault.premium_balance_usdc = vault
+            .premium_balance_usdc
+            .checked_sub(amount)
             .ok_or(VaultError::Overflow)?;
 
-        emit!(PremiumCollectedEvent {
+        emit!(PremiumClaimedEvent {
             vault: vault.key(),
-            payer: ctx.accounts.payer.key(),
+            owner: ctx.accounts.owner.key(),
+            share_account: checkpoint.share_account,
             amount,
-            epoch: vault.epoch,
+            premium_index: vault.premium_per_share_index,
         });
 
         Ok(())
     }
 
-    /// Pay out to market maker for ITM settlement
-    /// Only callable by vault authority
-    /// SECURITY: Settlement capped at epoch premium earned
-    pub fn pay_settlement(ctx: Context<PaySettlement>, amount: u64) -> Result<()> {
-        require!(amount > 0, VaultError::ZeroAmount);
+    /// Create the PremiumCheckpoint of a share account (anyone may pay)
+    /// Share accounts need one before they can receive shares by transfer
+    pub fn open_premium_checkpoint(ctx: Context<OpenPremiumCheckpoint>) -> Result<()> {
+        let balance = ctx.accounts.share_account.amount;
+        sync_premium_checkpoint(
+            &mut ctx.accounts.premium_checkpoint,
+            &ctx.accounts.vault,
+            ctx.accounts.share_account.key(),
+            balance,
+            balance,
+            ctx.bumps.premium_checkpoint,
+        )
+    }
 
-        // SECURITY FIX C-1: Verify recipient is whitelisted BEFORE transfer
-        let whitelist = &ctx.accounts.whitelist;
-        require!(
-            whitelist
-                .market_makers
-                .contains(&ctx.accounts.recipient.key()),
-            VaultError::NotWhitelisted
-        );
+    /// Transfer hook of the share mint (spl-transfer-hook-interface Execute)
+    /// Accrues both sides up to the current index before the shares change
+    /// hands, so each share earns every credit exactly once whoever holds it.
+    /// Gated vaults only let shares move to attested wallets
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
+        // Only Token-2022 may invoke the hook, in the middle of a transfer
+        check_transferring(&ctx.accounts.source_share_account.to_account_info())?;
+
+        let source = &ctx.accounts.source_share_account;
+        let destination = &ctx.accounts.destination_share_account;
+        if source.key() == destination.key() {
+            return Ok(());
+        }
 
-        // SECURITY FIX H-2: Cap settlements at epoch premium earned
-        let vault = &ctx.accounts.vault;
+        let attestation_info = &ctx.accounts.destination_attestation;
+        let attestation = if attestation_info.owner == &crate::ID {
+            Attestation::try_deserialize(&mut &attestation_info.try_borrow_data()?[..]).ok()
+        } else {
+            None
+        };
+        ctx.accounts
+            .vault
+            .check_attestation(attestation.as_ref(), Clock::get()?.unix_timestamp)?;
+
+        move_premium_accrual(
+            ctx.accounts.vault.premium_per_share_index,
+            &mut ctx.accounts.source_checkpoint,
+            source.amount,
+            &mut ctx.accounts.destination_checkpoint,
+            destination.amount,
+            amount,
+        )
+    }
+
+    /// Process withdrawal after epoch settles
+    /// Pays out at the EpochSnapshot price of the roll the shares exited at
+    /// SECURITY FIX H-3: Added min_expected_amount for slippage protection
+    /// SECURITY FIX M-3: Blocked when vault is paused
+    pub fn process_withdrawal(
+        ctx: Context<ProcessWithdrawal>,
+        min_expected_amount: u64,
+    ) -> Result<()> {
+        let withdrawal = &mut ctx.accounts.withdrawal_request;
+        let vault = &mut ctx.accounts.vault;
+
+        // SECURITY FIX M-3: Block withdrawals when paused (emergency protection)
+        require!(!vault.is_paused, VaultError::VaultPaused);
+
+        require!(!withdrawal.processed, VaultError::AlreadyProcessed);
         require!(
-            amount <= vault.epoch_premium_earned,
-            VaultError::ExcessiveSettlement
+            vault.epoch > withdrawal.request_epoch,
+            VaultError::EpochNotSettled
         );
 
+        let shares = withdrawal.shares;
+        // Payout is fixed by the snapshot of the roll that closed the request's
+        // epoch and paid from the withdrawal reserves
+        let WithdrawalPayout {
+            collateral: amount,
+            premium: user_premium_share,
+            underlying: user_underlying_share,
+            fee_shares,
+        } = vault.redeem_exited_withdrawal(
+            &ctx.accounts.epoch_snapshot,
+            shares,
+            withdrawal.premium_index_at_request,
+        )?;
+
+        // SECURITY FIX H-3: Slippage protection - user specifies minimum acceptable amount
+        require!(amount >= min_expected_amount, VaultError::SlippageExceeded);
+
+        // Withdrawn collateral frees up the user's deposit cap
+        if let Some(user_deposits) = ctx.accounts.user_deposits.as_mut() {
+            user_deposits.deposited = user_deposits.deposited.saturating_sub(amount);
+        }
+
         let asset_id = vault.asset_id.as_bytes();
         let seeds = &[b"vault", asset_id, &[vault.bump]];
         let signer_seeds = &[&seeds[..]];
 
-        token::transfer(
+        if fee_shares > 0 {
+            let fee_share_account = ctx
+                .accounts
+                .fee_share_account
+                .as_ref()
+                .ok_or(VaultError::FeeAccountMissing)?;
+            sync_fee_checkpoint(
+                ctx.accounts.fee_premium_checkpoint.as_mut(),
+                vault,
+                fee_share_account,
+                fee_shares,
+            )?;
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.share_mint.to_account_info(),
+                        to: fee_share_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee_shares,
+            )?;
+        }
+
+        // Premium available in the premium account (put vaults hold collateral there too)
+        let collateral_in_premium_account = vault.collateral_in_premium_account();
+        let actual_premium_balance = ctx
+            .accounts
+            .vault_premium_account
+            .amount
+            .saturating_sub(collateral_in_premium_account);
+
+        // Transfer collateral back to user
+        let (collateral_from, collateral_to, collateral_mint, collateral_program) =
+            match vault.strategy {
+                Strategy::CoveredCall => (
+                    ctx.accounts.vault_token_account.to_account_info(),
+                    ctx.accounts.user_token_account.to_account_info(),
+                    &ctx.accounts.underlying_mint,
+                    &ctx.accounts.underlying_token_program,
+                ),
+                Strategy::CashSecuredPut => (
+                    ctx.accounts.vault_premium_account.to_account_info(),
+                    ctx.accounts.user_premium_account.to_account_info(),
+                    &ctx.accounts.premium_mint,
+                    &ctx.accounts.premium_token_program,
+                ),
+            };
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault_premium_account.to_account_info(),
-                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                collateral_program.to_account_info(),
+                TransferChecked {
+                    from: collateral_from,
+                    mint: collateral_mint.to_account_info(),
+                    to: collateral_to,
                     authority: vault.to_account_info(),
                 },
                 signer_seeds,
             ),
             amount,
+            collateral_mint.decimals,
         )?;
 
-        emit!(SettlementPaidEvent {
+        // Put vaults: proportional share of underlying assigned on ITM puts
+        let capped_underlying_share =
+            user_underlying_share.min(ctx.accounts.vault_token_account.amount);
+        if capped_underlying_share > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.underlying_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        mint: ctx.accounts.underlying_mint.to_account_info(),
+                        to: ctx.accounts.user_token_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                capped_underlying_share,
+                ctx.accounts.underlying_mint.decimals,
+            )?;
+
+            msg!("Withdrew assigned underlying: {}", capped_underlying_share);
+        }
+
+        // Premium credited between the request and the exit roll (earlier
+        // premium was checkpointed at request_withdrawal and is paid by claim_premium)
+        // LONG-TERM FIX: Cap claim to actual token balance to handle state/balance drift
+        // This prevents "insufficient funds" errors if state drifts from actual balance
+        let capped_premium_share = user_premium_share.min(actual_premium_balance);
+        if capped_premium_share > 0 {
+            // Transfer USDC to user
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.premium_token_program.to_account_info(), // Standard token program for USDC
+                    TransferChecked {
+                        from: ctx.accounts.vault_premium_account.to_account_info(),
+                        mint: ctx.accounts.premium_mint.to_account_info(),
+                        to: ctx.accounts.user_premium_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                capped_premium_share,
+                ctx.accounts.premium_mint.decimals,
+            )?;
+
+            if capped_premium_share < user_premium_share {
+                msg!(
+                    "WARNING: Premium capped from {} to {} due to balance drift",
+                    user_premium_share,
+                    capped_premium_share
+                );
+            }
+            msg!("Withdrew premium share: {} USDC", capped_premium_share);
+        }
+
+        // Mark withdrawal as processed
+        withdrawal.processed = true;
+
+        emit!(WithdrawalProcessedEvent {
             vault: vault.key(),
-            recipient: ctx.accounts.recipient.key(),
+            user: ctx.accounts.user.key(),
+            shares,
             amount,
+            fee_shares,
             epoch: vault.epoch,
         });
 
         Ok(())
     }
 
-    /// Create metadata for the share token (vNVDAx, etc.)
-    /// Only callable by vault authority since vault PDA is the mint authority
-    pub fn create_share_metadata(
-        ctx: Context<CreateShareMetadata>,
-        name: String,
-        symbol: String,
-        uri: String,
-    ) -> Result<()> {
-        let vault = &ctx.accounts.vault;
+    /// Advance epoch (called by keeper after settlement)
+    /// Credits the premium received on-chain this epoch (epoch_premium_received)
+    pub fn advance_epoch(ctx: Context<AdvanceEpoch>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        // Every position sold this epoch must be settled before rolling
+        require!(vault.open_positions == 0, VaultError::OpenPositions);
+
+        // 1. Timelock Check
+        require!(
+            clock.unix_timestamp >= vault.last_roll_timestamp + vault.min_epoch_duration,
+            VaultError::EpochTooShort
+        );
+
+        // 2. Premium is what reached the premium account this epoch, so unpaid
+        // fills and keeper input cannot inflate it
+        let premium_earned = vault.epoch_premium_received;
+
+        // Store epoch stats before resetting
+        let closed_epoch = vault.epoch;
+        let epoch_start = vault.last_roll_timestamp;
+        let notional_exposed = vault.epoch_notional_exposed;
+        let avg_premium_bps = vault.epoch_premium_per_token_bps;
+
+        // Epochs that sold options have a settlement record; it must be passed so
+        // the history shows what was paid out
+        let (settlement_paid, settlement_price) = match &ctx.accounts.epoch_settlement {
+            Some(settlement) => (settlement.total_paid, settlement.settlement_price),
+            None => {
+                require!(notional_exposed == 0, VaultError::EpochSettlementMissing);
+                (0, 0)
+            }
+        };
+        // Epochs without a settlement are valued at the last settlement price
+        if settlement_price > 0 {
+            vault.last_settlement_price = settlement_price;
+        }
+        let valuation_price = vault.last_settlement_price;
+
+        // 4. Fees
+        // Performance fee is skimmed from premium only while total value per
+        // share (collateral plus premium paid out) including this epoch's
+        // premium is at or above the high-water mark (prior losses must be
+        // recovered)
+        let value_per_share = vault
+            .value_per_share(valuation_price)?
+            .checked_add(vault.per_share(premium_earned)?)
+            .ok_or(VaultError::Overflow)?;
+        let at_high_water_mark = value_per_share >= vault.high_water_mark;
+        let performance_fee = if at_high_water_mark {
+            bps_of(premium_earned, vault.performance_fee_bps)?
+        } else {
+            0
+        };
+        // Management fee accrues per second and is paid by minting shares
+        let elapsed = clock
+            .unix_timestamp
+            .saturating_sub(vault.last_roll_timestamp)
+            .max(0) as u128;
+        let management_fee_shares = ((vault.total_shares as u128)
+            .checked_mul(vault.management_fee_bps as u128)
+            .ok_or(VaultError::Overflow)?
+            .checked_mul(elapsed)
+            .ok_or(VaultError::Overflow)?
+            .checked_div(10000 * SECONDS_PER_YEAR)
+            .ok_or(VaultError::Overflow)?) as u64;
+
         let asset_id = vault.asset_id.as_bytes();
         let seeds = &[b"vault", asset_id, &[vault.bump]];
         let signer_seeds = &[&seeds[..]];
 
-        // Build the CreateMetadataAccountV3 instruction
-        let create_metadata_ix = mpl_token_metadata::instructions::CreateMetadataAccountV3 {
-            metadata: ctx.accounts.metadata.key(),
-            mint: ctx.accounts.share_mint.key(),
-            mint_authority: vault.key(),
-            payer: ctx.accounts.payer.key(),
-            update_authority: (vault.key(), true),
-            system_program: ctx.accounts.system_program.key(),
-            rent: Some(ctx.accounts.rent.key()),
-        };
+        if performance_fee > 0 {
+            let fee_premium_account = ctx
+                .accounts
+                .fee_premium_account
+                .as_ref()
+                .ok_or(VaultError::FeeAccountMissing)?;
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.premium_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault_premium_account.to_account_info(),
+                        mint: ctx.accounts.premium_mint.to_account_info(),
+                        to: fee_premium_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                performance_fee,
+                ctx.accounts.premium_mint.decimals,
+            )?;
+        }
 
-        let data_v2 = mpl_token_metadata::types::DataV2 {
-            name,
-            symbol,
-            uri,
-            seller_fee_basis_points: 0,
-            creators: None,
-            collection: None,
-            uses: None,
+        if management_fee_shares > 0 {
+            let fee_share_account = ctx
+                .accounts
+                .fee_share_account
+                .as_ref()
+                .ok_or(VaultError::FeeAccountMissing)?;
+            sync_fee_checkpoint(
+                ctx.accounts.fee_premium_checkpoint.as_mut(),
+                vault,
+                fee_share_account,
+                management_fee_shares,
+            )?;
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.share_mint.to_account_info(),
+                        to: fee_share_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                management_fee_shares,
+            )?;
+        }
+
+        vault.total_shares = vault
+            .total_shares
+            .checked_add(management_fee_shares)
+            .ok_or(VaultError::Overflow)?;
+
+        let net_premium = premium_earned - performance_fee;
+        let premium_compounded = vault.book_epoch_premium(net_premium)?;
+        if at_high_water_mark {
+            vault.high_water_mark = vault.value_per_share(valuation_price)?;
+        }
+
+        vault.epoch = vault.epoch.checked_add(1).ok_or(VaultError::Overflow)?;
+        vault.last_roll_timestamp = clock.unix_timestamp;
+
+        // 5. Exit withdrawals requested this epoch at the post-fee price-per-share
+        let snapshot = &mut ctx.accounts.epoch_snapshot;
+        let withdrawal_amount = vault.exit_queued_withdrawals(snapshot)?;
+        let withdrawal_shares = snapshot.withdrawal_shares;
+        let price_per_share = snapshot.price_per_share;
+
+        // 6. Admit queued deposits at the same price-per-share
+        // Shares are accounted here and minted lazily by claim_deposit
+        let deposits_admitted = vault.pending_deposits;
+        let deposit_shares = if deposits_admitted > 0 {
+            require!(price_per_share > 0, VaultError::DivisionByZero);
+            if vault.total_shares.checked_add(vault.virtual_offset) == Some(0) {
+                // First capital into the vault: same virtual offset as deposit
+                vault.virtual_offset = 1000;
+            }
+            (deposits_admitted as u128)
+                .checked_mul(PRICE_PER_SHARE_SCALE)
+                .ok_or(VaultError::Overflow)?
+                .checked_div(price_per_share as u128)
+                .ok_or(VaultError::Overflow)? as u64
+        } else {
+            0
         };
+        vault.total_assets = vault
+            .total_assets
+            .checked_add(deposits_admitted)
+            .ok_or(VaultError::Overflow)?;
+        vault.total_shares = vault
+            .total_shares
+            .checked_add(deposit_shares)
+            .ok_or(VaultError::Overflow)?;
+        vault.pending_deposits = 0;
+
+        snapshot.vault = vault.key();
+        snapshot.epoch = closed_epoch;
+        snapshot.deposits_admitted = deposits_admitted;
+        snapshot.deposit_shares = deposit_shares;
+        snapshot.start_timestamp = epoch_start;
+        snapshot.end_timestamp = clock.unix_timestamp;
+        snapshot.notional_exposed = notional_exposed;
+        snapshot.premium_earned = premium_earned;
+        snapshot.avg_premium_bps = avg_premium_bps;
+        snapshot.settlement_paid = settlement_paid;
+        snapshot.settlement_price = settlement_price;
+        snapshot.bump = ctx.bumps.epoch_snapshot;
 
-        let ix = create_metadata_ix.instruction(
-            mpl_token_metadata::instructions::CreateMetadataAccountV3InstructionArgs {
-                data: data_v2,
-                is_mutable: true,
-                collection_details: None,
-            },
-        );
+        // Reset epoch tracking for new epoch
+        vault.epoch_notional_exposed = 0;
+        vault.epoch_premium_earned = 0;
+        vault.epoch_premium_received = 0;
+        vault.epoch_premium_per_token_bps = 0;
 
-        anchor_lang::solana_program::program::invoke_signed(
-            &ix,
-            &[
-                ctx.accounts.metadata.to_account_info(),
-                ctx.accounts.share_mint.to_account_info(),
-                vault.to_account_info(),
-                ctx.accounts.payer.to_account_info(),
-                vault.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-                ctx.accounts.rent.to_account_info(),
-            ],
-            signer_seeds,
-        )?;
+        emit!(EpochAdvancedEvent {
+            vault: vault.key(),
+            new_epoch: vault.epoch,
+            premium_earned,
+            notional_exposed,
+            avg_premium_bps,
+            total_assets: vault.total_assets,
+            total_shares: vault.total_shares,
+            premium_balance_usdc: vault.premium_balance_usdc,
+            management_fee_shares,
+            performance_fee,
+            high_water_mark: vault.high_water_mark,
+            premium_compounded,
+            withdrawal_shares,
+            withdrawal_amount,
+            deposits_admitted,
+            deposit_shares,
+        });
 
-        msg!(
-            "Created metadata for share token: {}",
-            ctx.accounts.share_mint.key()
-        );
         Ok(())
     }
 
-    /// Initialize the market maker whitelist
-    pub fn initialize_whitelist(ctx: Context<InitializeWhitelist>) -> Result<()> {
-        let whitelist = &mut ctx.accounts.whitelist;
-        whitelist.authority = ctx.accounts.authority.key();
-        whitelist.vault = ctx.accounts.vault.key();
-        whitelist.market_makers = Vec::new(); // Maximum 10 MMs
-        whitelist.bump = ctx.bumps.whitelist;
-        Ok(())
-    }
+    /// Swap premium set aside for compounding into underlying and credit
+    /// total_assets (keeper only)
+    /// The swap program spends a delegated allowance of amount_in held by the
+    /// swap_authority PDA; the result is verified from token balances
+    pub fn compound_premium<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CompoundPremium<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        require!(amount_in > 0, VaultError::ZeroAmount);
 
-    /// Add a market maker to the whitelist
-    pub fn add_market_maker(ctx: Context<AddMarketMaker>, market_maker: Pubkey) -> Result<()> {
-        let whitelist = &mut ctx.accounts.whitelist;
+        let vault = &ctx.accounts.vault;
+        require!(
+            vault.strategy == Strategy::CoveredCall,
+            VaultError::InvalidStrategy
+        );
         require!(
-            whitelist.market_makers.len() < 10,
-            VaultError::WhitelistFull
+            vault.swap_program != Pubkey::default(),
+            VaultError::InvalidCompoundConfig
         );
         require!(
-            !whitelist.market_makers.contains(&market_maker),
-            VaultError::AlreadyWhitelisted
+            amount_in <= vault.premium_to_compound,
+            VaultError::InsufficientPremiumToCompound
         );
 
-        whitelist.market_makers.push(market_maker);
-        Ok(())
-    }
+        let premium_before = ctx.accounts.vault_premium_account.amount;
+        let underlying_before = ctx.accounts.vault_token_account.amount;
 
-    /// SECURITY FIX M-4: Remove a market maker from the whitelist
-    pub fn remove_market_maker(
-        ctx: Context<RemoveMarketMaker>,
-        market_maker: Pubkey,
-    ) -> Result<()> {
-        let whitelist = &mut ctx.accounts.whitelist;
+        let asset_id = vault.asset_id.as_bytes();
+        let seeds = &[b"vault", asset_id, &[vault.bump]];
+        let signer_seeds = &[&seeds[..]];
 
-        let position = whitelist
-            .market_makers
-            .iter()
-            .position(|&mm| mm == market_maker);
-        require!(position.is_some(), VaultError::NotWhitelisted);
+        // Limit what the swap program can move to amount_in
+        token_interface::approve(
+            CpiContext::new_with_signer(
+                ctx.accounts.premium_token_program.to_account_info(),
+                Approve {
+                    to: ctx.accounts.vault_premium_account.to_account_info(),
+                    delegate: ctx.accounts.swap_authority.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_in,
+        )?;
 
-        whitelist.market_makers.remove(position.unwrap());
+        let mut accounts = vec![
+            AccountMeta::new(ctx.accounts.vault_premium_account.key(), false),
+            AccountMeta::new(ctx.accounts.vault_token_account.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.swap_authority.key(), true),
+            AccountMeta::new_readonly(ctx.accounts.premium_mint.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.underlying_mint.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.premium_token_program.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.underlying_token_program.key(), false),
+        ];
+        let mut account_infos = vec![
+            ctx.accounts.vault_premium_account.to_account_info(),
+            ctx.accounts.vault_token_account.to_account_info(),
+            ctx.accounts.swap_authority.to_account_info(),
+            ctx.accounts.premium_mint.to_account_info(),
+            ctx.accounts.underlying_mint.to_account_info(),
+            ctx.accounts.premium_token_program.to_account_info(),
+            ctx.accounts.underlying_token_program.to_account_info(),
+        ];
+        for account in ctx.remaining_accounts {
+            accounts.push(if account.is_writable {
+                AccountMeta::new(account.key(), account.is_signer)
+            } else {
+                AccountMeta::new_readonly(account.key(), account.is_signer)
+            });
+            account_infos.push(account.clone());
+        }
+        account_infos.push(ctx.accounts.swap_program.to_account_info());
+
+        let mut data = SWAP_IX_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+        let vault_key = vault.key();
+        let swap_authority_seeds = &[
+            b"swap_authority",
+            vault_key.as_ref(),
+            &[ctx.bumps.swap_authority],
+        ];
+        anchor_lang::solana_program::program::invoke_signed(
+            &Instruction {
+                program_id: vault.swap_program,
+                accounts,
+                data,
+            },
+            &account_infos,
+            &[&swap_authority_seeds[..]],
+        )?;
 
-        emit!(MarketMakerRemovedEvent {
-            vault: ctx.accounts.vault.key(),
-            market_maker,
-        });
+        token_interface::revoke(CpiContext::new_with_signer(
+            ctx.accounts.premium_token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.vault_premium_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
 
-        Ok(())
-    }
+        // Verify the swap from balances rather than trusting the swap program
+        ctx.accounts.vault_premium_account.reload()?;
+        ctx.accounts.vault_token_account.reload()?;
+        let premium_spent = premium_before
+            .checked_sub(ctx.accounts.vault_premium_account.amount)
+            .ok_or(VaultError::InvalidSwapResult)?;
+        let underlying_received = ctx
+            .accounts
+            .vault_token_account
+            .amount
+            .checked_sub(underlying_before)
+            .ok_or(VaultError::InvalidSwapResult)?;
+        require!(
+            underlying_received >= min_amount_out,
+            VaultError::SlippageExceeded
+        );
 
-    /// SECURITY: Pause or unpause the vault (emergency control)
-    /// When paused, deposits and withdrawal requests are blocked
-    pub fn set_pause(ctx: Context<SetPause>, paused: bool) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
-        vault.is_paused = paused;
+        vault.record_compounded(premium_spent, underlying_received)?;
 
-        emit!(VaultPausedEvent {
+        emit!(PremiumCompoundedEvent {
             vault: vault.key(),
-            paused,
-            timestamp: Clock::get()?.unix_timestamp,
+            premium_spent,
+            underlying_received,
+            total_assets: vault.total_assets,
+            price_per_share: vault.price_per_share()?,
         });
 
         Ok(())
     }
 
-    /// SECURITY FIX M-3: Queue a parameter change with timelock
-    /// Changes take effect after TIMELOCK_DURATION (24 hours)
-    pub fn queue_param_change(
-        ctx: Context<SetParam>,
-        new_min_epoch_duration: Option<i64>,
-        new_utilization_cap_bps: Option<u16>,
+    /// Record notional exposure when an RFQ is filled (fractional options)
+    /// Premium is in premium_mint tokens (USDC)
+    /// Strike is in premium_mint base units per whole underlying token
+    /// Each fill opens an OptionPosition that expires at the end of the epoch
+    /// The premium is owed by the maker (see pay_premium) and must be covered
+    /// by its collateral, which is slashable after PREMIUM_GRACE_WINDOW
+    pub fn record_notional_exposure(
+        ctx: Context<RecordNotionalExposure>,
+        notional_tokens: u64,
+        premium: u64,
+        strike_price: u64,
+        market_maker: Pubkey,
     ) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        let clock = Clock::get()?;
+        check_underlying_multiplier(&ctx.accounts.vault, &ctx.accounts.underlying_mint)?;
+
+        let accounts = &mut *ctx.accounts;
+        open_position(
+            &mut accounts.vault,
+            &mut accounts.market_maker_account,
+            &mut accounts.epoch_settlement,
+            ctx.bumps.epoch_settlement,
+            &mut accounts.position,
+            ctx.bumps.position,
+            FillTerms {
+                market_maker,
+                notional_tokens,
+                premium,
+                strike_price,
+            },
+        )?;
 
-        // Store pending changes
-        if let Some(duration) = new_min_epoch_duration {
-            vault.pending_min_epoch_duration = duration;
-        }
-        if let Some(cap) = new_utilization_cap_bps {
-            require!(cap <= 10000, VaultError::InvalidParameter); // Max 100%
-            vault.pending_utilization_cap = cap;
+        let maker = &mut accounts.market_maker_account;
+        maker.unpaid_premium = maker
+            .unpaid_premium
+            .checked_add(premium)
+            .ok_or(VaultError::Overflow)?;
+        maker.check_collateral()?;
+        if maker.premium_due_at == 0 {
+            maker.premium_due_at = Clock::get()?
+                .unix_timestamp
+                .checked_add(PREMIUM_GRACE_WINDOW)
+                .ok_or(VaultError::Overflow)?;
         }
 
-        // Set execution time (24 hours from now)
-        const TIMELOCK_DURATION: i64 = 86400; // 24 hours
-        vault.param_change_unlock_time = clock
-            .unix_timestamp
-            .checked_add(TIMELOCK_DURATION)
-            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
 
-        emit!(ParamChangeQueuedEvent {
-            vault: vault.key(),
-            new_min_epoch_duration,
-            new_utilization_cap_bps,
-            unlock_time: vault.param_change_unlock_time,
+    /// Fill an RFQ atomically (market maker and vault keeper sign)
+    /// Moves premium from the market maker, checks exposure against the
+    /// utilization cap and opens the OptionPosition in one transaction
+    pub fn fill_rfq(
+        ctx: Context<FillRfq>,
+        notional_tokens: u64,
+        premium: u64,
+        strike_price: u64,
+    ) -> Result<()> {
+        require!(premium > 0, VaultError::ZeroAmount);
+        check_underlying_multiplier(&ctx.accounts.vault, &ctx.accounts.underlying_mint)?;
+
+        // Premium is recorded as received (net of Token-2022 transfer fees)
+        let premium = transfer_in(
+            &ctx.accounts.premium_token_program,
+            ctx.accounts.market_maker_token_account.to_account_info(),
+            &mut ctx.accounts.vault_premium_account,
+            &ctx.accounts.premium_mint,
+            ctx.accounts.market_maker.to_account_info(),
+            premium,
+            &[],
+        )?;
+
+        let market_maker = ctx.accounts.market_maker.key();
+        let accounts = &mut *ctx.accounts;
+        open_position(
+            &mut accounts.vault,
+            &mut accounts.market_maker_account,
+            &mut accounts.epoch_settlement,
+            ctx.bumps.epoch_settlement,
+            &mut accounts.position,
+            ctx.bumps.position,
+            FillTerms {
+                market_maker,
+                notional_tokens,
+                premium,
+                strike_price,
+            },
+        )?;
+
+        accounts.market_maker_account.record_premium_paid(premium)?;
+        accounts.vault.record_premium_received(premium)?;
+
+        emit!(PremiumCollectedEvent {
+            vault: accounts.vault.key(),
+            payer: market_maker,
+            amount: premium,
+            epoch: accounts.vault.epoch,
         });
 
         Ok(())
     }
 
-    /// SECURITY FIX M-3: Execute queued parameter changes after timelock
-    pub fn execute_param_change(ctx: Context<SetParam>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
+    /// Fill an RFQ from a quote signed by the market maker's key
+    /// The keeper submits the winning quote on the maker's behalf; the quote
+    /// signature is checked against the preceding Ed25519 program instruction.
+    /// Premium is pulled from the maker's token account, which must have
+    /// approved the vault PDA as delegate for at least the quoted premium.
+    pub fn fill_signed_quote(ctx: Context<FillSignedQuote>, quote: RfqQuote) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        let market_maker = ctx.accounts.market_maker.key();
         let clock = Clock::get()?;
 
-        // Check timelock has passed
+        verify_quote_signature(
+            &ctx.accounts.instructions_sysvar,
+            &market_maker,
+            &quote.try_to_vec()?,
+        )?;
+
+        require!(quote.vault == vault_key, VaultError::QuoteMismatch);
         require!(
-            vault.param_change_unlock_time > 0
-                && clock.unix_timestamp >= vault.param_change_unlock_time,
-            VaultError::TimelockNotExpired
+            clock.unix_timestamp <= quote.deadline,
+            VaultError::QuoteExpired
         );
+        let expiry = ctx
+            .accounts
+            .vault
+            .last_roll_timestamp
+            .checked_add(ctx.accounts.vault.min_epoch_duration)
+            .ok_or(VaultError::Overflow)?;
+        require!(quote.expiry == expiry, VaultError::QuoteMismatch);
+        require!(quote.premium > 0, VaultError::ZeroAmount);
+        check_underlying_multiplier(&ctx.accounts.vault, &ctx.accounts.underlying_mint)?;
 
-        // Apply pending changes
-        if vault.pending_min_epoch_duration > 0 {
-            vault.min_epoch_duration = vault.pending_min_epoch_duration;
-        }
-        if vault.pending_utilization_cap > 0 {
-            vault.utilization_cap_bps = vault.pending_utilization_cap;
-        }
+        let asset_id = ctx.accounts.vault.asset_id.clone();
+        let seeds = &[b"vault", asset_id.as_bytes(), &[ctx.accounts.vault.bump]];
+        let signer_seeds = &[&seeds[..]];
 
-        emit!(ParamChangeExecutedEvent {
-            vault: vault.key(),
-            new_min_epoch_duration: vault.min_epoch_duration,
-            new_utilization_cap_bps: vault.utilization_cap_bps,
-        });
+        // Vault PDA spends the maker's delegated allowance
+        // Premium is recorded as received (net of Token-2022 transfer fees)
+        let premium = transfer_in(
+            &ctx.accounts.premium_token_program,
+            ctx.accounts.market_maker_token_account.to_account_info(),
+            &mut ctx.accounts.vault_premium_account,
+            &ctx.accounts.premium_mint,
+            ctx.accounts.vault.to_account_info(),
+            quote.premium,
+            signer_seeds,
+        )?;
+
+        let receipt = &mut ctx.accounts.quote_receipt;
+        receipt.vault = vault_key;
+        receipt.market_maker = market_maker;
+        receipt.rfq_id = quote.rfq_id;
+        receipt.position = ctx.accounts.position.key();
+        receipt.bump = ctx.bumps.quote_receipt;
+
+        let accounts = &mut *ctx.accounts;
+        open_position(
+            &mut accounts.vault,
+            &mut accounts.market_maker_account,
+            &mut accounts.epoch_settlement,
+            ctx.bumps.epoch_settlement,
+            &mut accounts.position,
+            ctx.bumps.position,
+            FillTerms {
+                market_maker,
+                notional_tokens: quote.notional,
+                premium,
+                strike_price: quote.strike_price,
+            },
+        )?;
+
+        accounts.market_maker_account.record_premium_paid(premium)?;
+        accounts.vault.record_premium_received(premium)?;
 
-        // Reset pending state
-        vault.pending_min_epoch_duration = 0;
-        vault.pending_utilization_cap = 0;
-        vault.param_change_unlock_time = 0;
+        emit!(SignedQuoteFilledEvent {
+            vault: vault_key,
+            market_maker,
+            rfq_id: quote.rfq_id,
+            position: accounts.position.key(),
+            premium,
+        });
 
         Ok(())
     }
 
-    /// Cancel a queued parameter change
-    pub fn cancel_param_change(ctx: Context<SetParam>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
+    /// Deposit premium_mint collateral backing the maker's unpaid premium and
+    /// open positions (market maker signs)
+    pub fn deposit_maker_collateral(
+        ctx: Context<DepositMakerCollateral>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, VaultError::ZeroAmount);
 
-        vault.pending_min_epoch_duration = 0;
-        vault.pending_utilization_cap = 0;
-        vault.param_change_unlock_time = 0;
+        // Credited net of Token-2022 transfer fees
+        let amount = transfer_in(
+            &ctx.accounts.premium_token_program,
+            ctx.accounts.market_maker_token_account.to_account_info(),
+            &mut ctx.accounts.maker_collateral_account,
+            &ctx.accounts.premium_mint,
+            ctx.accounts.market_maker.to_account_info(),
+            amount,
+            &[],
+        )?;
+
+        let maker = &mut ctx.accounts.market_maker_account;
+        maker.collateral = maker
+            .collateral
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+
+        emit!(MakerCollateralUpdatedEvent {
+            vault: ctx.accounts.vault.key(),
+            market_maker: maker.market_maker,
+            deposited: amount,
+            withdrawn: 0,
+            collateral: maker.collateral,
+        });
 
         Ok(())
     }
 
-    /// DEPRECATED: Direct parameter changes now require timelock
-    /// Kept for backwards compatibility but will fail
-    pub fn set_min_epoch_duration(_ctx: Context<SetParam>, _duration: i64) -> Result<()> {
-        Err(VaultError::UseTimelockForParamChange.into())
-    }
+    /// Withdraw collateral not backing unpaid premium or open positions
+    /// (market maker signs)
+    pub fn withdraw_maker_collateral(
+        ctx: Context<WithdrawMakerCollateral>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, VaultError::ZeroAmount);
 
-    /// DEPRECATED: Direct parameter changes now require timelock
-    /// Kept for backwards compatibility but will fail
-    pub fn set_utilization_cap(_ctx: Context<SetParam>, _cap_bps: u16) -> Result<()> {
-        Err(VaultError::UseTimelockForParamChange.into())
-    }
-
-    /// Reconcile premium_balance_usdc state with actual token account balance
-    /// Use this to fix state drift caused by manual token movements or bugs
-    /// Authority only
-    pub fn reconcile_premium_balance(ctx: Context<ReconcilePremiumBalance>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        let old_balance = vault.premium_balance_usdc;
-        let actual_balance = ctx.accounts.vault_premium_account.amount;
+        let maker = &mut ctx.accounts.market_maker_account;
+        maker.collateral = maker
+            .collateral
+            .checked_sub(amount)
+            .ok_or(VaultError::InsufficientMakerCollateral)?;
+        maker.check_collateral()?;
 
-        vault.premium_balance_usdc = actual_balance;
+        let vault = &ctx.accounts.vault;
+        let asset_id = vault.asset_id.as_bytes();
+        let seeds = &[b"vault", asset_id, &[vault.bump]];
+        let signer_seeds = &[&seeds[..]];
 
-        msg!(
-            "Reconciled premium balance: {} -> {}",
-            old_balance,
-            actual_balance
-        );
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.premium_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.maker_collateral_account.to_account_info(),
+                    mint: ctx.accounts.premium_mint.to_account_info(),
+                    to: ctx.accounts.market_maker_token_account.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.premium_mint.decimals,
+        )?;
 
-        emit!(PremiumBalanceReconciledEvent {
+        emit!(MakerCollateralUpdatedEvent {
             vault: vault.key(),
-            old_balance,
-            new_balance: actual_balance,
+            market_maker: maker.market_maker,
+            deposited: 0,
+            withdrawn: amount,
+            collateral: maker.collateral,
         });
 
         Ok(())
     }
 
-    /// Close a vault and recover rent (authority only)
-    /// Vault must be empty (no assets, shares, or pending withdrawals)
-    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
-        let vault = &ctx.accounts.vault;
+    /// Pay premium owed for fills recorded via record_notional_exposure
+    /// (market maker signs)
+    pub fn pay_premium(ctx: Context<PayPremium>, amount: u64) -> Result<()> {
+        require!(amount > 0, VaultError::ZeroAmount);
+        require!(
+            amount <= ctx.accounts.market_maker_account.unpaid_premium,
+            VaultError::InvalidParameter
+        );
 
-        // Safety checks - vault must be completely empty
-        require!(vault.total_assets == 0, VaultError::VaultNotEmpty);
-        require!(vault.total_shares == 0, VaultError::VaultNotEmpty);
-        require!(vault.pending_withdrawals == 0, VaultError::VaultNotEmpty);
-        require!(vault.epoch_notional_exposed == 0, VaultError::VaultNotEmpty);
+        // Debt is reduced by the amount received (net of Token-2022 transfer fees)
+        let amount = transfer_in(
+            &ctx.accounts.premium_token_program,
+            ctx.accounts.market_maker_token_account.to_account_info(),
+            &mut ctx.accounts.vault_premium_account,
+            &ctx.accounts.premium_mint,
+            ctx.accounts.market_maker.to_account_info(),
+            amount,
+            &[],
+        )?;
+
+        let maker = &mut ctx.accounts.market_maker_account;
+        maker.unpaid_premium = maker
+            .unpaid_premium
+            .checked_sub(amount)
+            .ok_or(VaultError::Overflow)?;
+        if maker.unpaid_premium == 0 {
+            maker.premium_due_at = 0;
+        }
+        maker.record_premium_paid(amount)?;
+        ctx.accounts.vault.record_premium_received(amount)?;
+
+        emit!(PremiumCollectedEvent {
+            vault: ctx.accounts.vault.key(),
+            payer: maker.market_maker,
+            amount,
+            epoch: ctx.accounts.vault.epoch,
+        });
 
-        // Account will be closed automatically by Anchor's close constraint
         Ok(())
     }
 
-    /// Force close a vault account (bypasses deserialization)
-    /// USE WITH CAUTION: Only for recovering from incompatible account structures
-    /// SECURITY FIX C-2: Now verifies caller is the stored vault authority
-    pub fn force_close_vault(ctx: Context<ForceCloseVault>, asset_id: String) -> Result<()> {
-        let vault_account = &ctx.accounts.vault;
-        let authority = &ctx.accounts.authority;
-
-        // Verify the PDA matches what we expect
-        let (expected_pda, _bump) =
-            Pubkey::find_program_address(&[b"vault", asset_id.as_bytes()], ctx.program_id);
+    /// Slash a maker's collateral for premium unpaid past PREMIUM_GRACE_WINDOW
+    /// (keeper only). The slashed collateral is paid into the premium account
+    /// in place of the premium and the maker is removed from the whitelist
+    pub fn slash_maker_collateral(ctx: Context<SlashMakerCollateral>) -> Result<()> {
+        let maker = &ctx.accounts.market_maker_account;
+        require!(maker.unpaid_premium > 0, VaultError::ZeroAmount);
         require!(
-            vault_account.key() == expected_pda,
-            VaultError::InvalidVaultPda
+            Clock::get()?.unix_timestamp >= maker.premium_due_at,
+            VaultError::PremiumGraceWindowOpen
         );
 
-        // SECURITY FIX C-2: Verify caller is the vault authority
-        // Authority is stored at bytes 8-40 (after 8-byte discriminator)
-        let vault_data = vault_account.try_borrow_data()?;
-        require!(vault_data.len() >= 40, VaultError::InvalidVaultPda);
+        let slashed = maker.unpaid_premium.min(maker.collateral);
+        let asset_id = ctx.accounts.vault.asset_id.clone();
+        let seeds = &[b"vault", asset_id.as_bytes(), &[ctx.accounts.vault.bump]];
+        let signer_seeds = &[&seeds[..]];
 
-        let stored_authority =
-            Pubkey::try_from(&vault_data[8..40]).map_err(|_| VaultError::InvalidVaultPda)?;
+        let received = if slashed > 0 {
+            transfer_in(
+                &ctx.accounts.premium_token_program,
+                ctx.accounts.maker_collateral_account.to_account_info(),
+                &mut ctx.accounts.vault_premium_account,
+                &ctx.accounts.premium_mint,
+                ctx.accounts.vault.to_account_info(),
+                slashed,
+                signer_seeds,
+            )?
+        } else {
+            0
+        };
+
+        let maker = &mut ctx.accounts.market_maker_account;
+        maker.collateral = maker
+            .collateral
+            .checked_sub(slashed)
+            .ok_or(VaultError::Overflow)?;
+        maker.unpaid_premium = maker
+            .unpaid_premium
+            .checked_sub(slashed)
+            .ok_or(VaultError::Overflow)?;
+        if maker.unpaid_premium == 0 {
+            maker.premium_due_at = 0;
+        }
+        maker.record_premium_paid(received)?;
+        maker.status = MarketMakerStatus::Removed;
+        ctx.accounts.vault.record_premium_received(received)?;
+
+        emit!(MakerSlashedEvent {
+            vault: ctx.accounts.vault.key(),
+            market_maker: maker.market_maker,
+            slashed,
+            received,
+            unpaid_premium: maker.unpaid_premium,
+            collateral: maker.collateral,
+        });
+
+        Ok(())
+    }
+
+    /// Collect premium from market maker (called during epoch roll)
+    /// Transfers USDC from payer to vault's premium account
+    /// SECURITY FIX M-1: Now requires keeper signature to prevent front-running
+    /// SECURITY FIX M-1b: Now updates epoch_premium_earned to track state
+    pub fn collect_premium(ctx: Context<CollectPremium>, amount: u64) -> Result<()> {
+        require!(amount > 0, VaultError::ZeroAmount);
+
+        // Track the amount received (net of Token-2022 transfer fees)
+        let amount = transfer_in(
+            &ctx.accounts.premium_token_program,
+            ctx.accounts.payer_token_account.to_account_info(),
+            &mut ctx.accounts.vault_premium_account,
+            &ctx.accounts.premium_mint,
+            ctx.accounts.payer.to_account_info(),
+            amount,
+            &[],
+        )?;
+
+        let vault = &mut ctx.accounts.vault;
+
+        // SECURITY FIX M-1b: Track collected premium in vault state
+        vault.epoch_premium_earned = vault
+            .epoch_premium_earned
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+        vault.record_premium_received(amount)?;
+
+        emit!(PremiumCollectedEvent {
+            vault: vault.key(),
+            payer: ctx.accounts.payer.key(),
+            amount,
+            epoch: vault.epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Settle the current epoch against the Pyth oracle price
+    /// Records the settlement price used by pay_settlement to compute each
+    /// position's ITM payout on-chain
+    pub fn settle_epoch(ctx: Context<SettleEpoch>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let settlement = &mut ctx.accounts.epoch_settlement;
+        let clock = Clock::get()?;
+
+        require!(!settlement.is_settled, VaultError::EpochAlreadySettled);
         require!(
-            stored_authority == authority.key(),
-            VaultError::UnauthorizedForceClose
+            vault.pending_underlying_multiplier == 0,
+            VaultError::CorporateActionPending
         );
-        drop(vault_data); // Release borrow before modifying
+        check_underlying_multiplier(vault, &ctx.accounts.underlying_mint)?;
 
-        // Transfer all lamports to authority
-        let vault_lamports = vault_account.lamports();
-        **vault_account.try_borrow_mut_lamports()? = 0;
-        **authority.try_borrow_mut_lamports()? = authority
-            .lamports()
-            .checked_add(vault_lamports)
+        // Options expire at the end of the epoch
+        let expiry = vault
+            .last_roll_timestamp
+            .checked_add(vault.min_epoch_duration)
             .ok_or(VaultError::Overflow)?;
+        require!(clock.unix_timestamp >= expiry, VaultError::EpochTooShort);
 
-        // Zero out the account data
-        vault_account.try_borrow_mut_data()?.fill(0);
+        // Price at expiry; settling late takes a historical price update
+        let price = load_oracle_price(vault, &ctx.accounts.price_update, expiry)?;
+
+        // Oracle quotes per UI token; convert at the current multiplier
+        let settlement_price =
+            vault.to_raw_price(price.to_premium_units(ctx.accounts.premium_mint.decimals)?)?;
+
+        settlement.settlement_price = settlement_price;
+        settlement.publish_time = price.publish_time;
+        settlement.underlying_decimals = ctx.accounts.underlying_mint.decimals;
+        settlement.settled_at = clock.unix_timestamp;
+        settlement.is_settled = true;
+
+        emit!(EpochSettledEvent {
+            vault: vault.key(),
+            epoch: settlement.epoch,
+            settlement_price,
+            publish_time: price.publish_time,
+            positions: settlement.position_count,
+        });
 
-        msg!(
-            "Force closed vault {} - returned {} lamports",
-            asset_id,
-            vault_lamports
-        );
         Ok(())
     }
 
-    /// Close an orphaned token account that was owned by a vault PDA
-    /// This is used to clean up old share escrows, vault token accounts, etc.
-    /// after a vault has been force-closed, enabling reuse of asset IDs.
-    ///
-    /// The caller must provide the correct asset_id that was used to derive the vault PDA.
-    /// The vault PDA must NOT exist anymore (force-closed).
-    pub fn close_orphaned_token_account(
-        ctx: Context<CloseOrphanedTokenAccount>,
-        asset_id: String,
-    ) -> Result<()> {
-        let token_account = &ctx.accounts.token_account;
-        let authority = &ctx.accounts.authority;
+    /// Settle a single option position and close it (settlement operator only)
+    /// ITM positions are cash-settled out of the vault's collateral and the
+    /// loss is debited from total_assets (lowering price-per-share):
+    /// - calls pay notional * (settlement_price - strike) / settlement_price underlying
+    /// - puts pay notional * (strike - settlement_price) / 10^underlying_decimals premium_mint
+    /// OTM positions are closed without a transfer
+    /// Physical-delivery vaults settle ITM positions via exercise_position;
+    /// positions not exercised within EXERCISE_WINDOW lapse and are closed here
+    pub fn pay_settlement(ctx: Context<PaySettlement>) -> Result<()> {
+        // SECURITY FIX C-1: Recipient is the position's counterparty and has a
+        // MarketMakerAccount (enforced by the account constraints)
+        let recipient = ctx.accounts.recipient.key();
+
+        let settlement = &mut ctx.accounts.epoch_settlement;
+        require!(settlement.is_settled, VaultError::EpochNotSettled);
+
+        let position = &ctx.accounts.position;
+        let value = position.intrinsic_value(settlement)?;
+        let vault = &mut ctx.accounts.vault;
 
-        // Derive vault PDA - this is the owner of the orphaned token account
-        let (vault_pda, bump) =
-            Pubkey::find_program_address(&[b"vault", asset_id.as_bytes()], ctx.program_id);
+        let amount = match vault.settlement_mode {
+            SettlementMode::Cash => position.collateral_payout(settlement)?,
+            SettlementMode::Physical => {
+                if value > 0 {
+                    let deadline = settlement
+                        .settled_at
+                        .checked_add(EXERCISE_WINDOW)
+                        .ok_or(VaultError::Overflow)?;
+                    require!(
+                        Clock::get()?.unix_timestamp >= deadline,
+                        VaultError::ExerciseWindowOpen
+                    );
+                    msg!("Physical position {} lapsed unexercised", position.key());
+                }
+                0
+            }
+        };
+
+        if amount > 0 {
+            require!(
+                vault.total_assets >= amount,
+                VaultError::InsufficientVaultBalance
+            );
+            let price_per_share_before = vault.price_per_share()?;
+
+            let asset_id = vault.asset_id.as_bytes();
+            let seeds = &[b"vault", asset_id, &[vault.bump]];
+            let signer_seeds = &[&seeds[..]];
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.collateral_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault_collateral_account.to_account_info(),
+                        mint: ctx.accounts.collateral_mint.to_account_info(),
+                        to: ctx.accounts.recipient_token_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+                ctx.accounts.collateral_mint.decimals,
+            )?;
+
+            // Depositors bear the option loss
+            vault.total_assets = vault
+                .total_assets
+                .checked_sub(amount)
+                .ok_or(VaultError::Overflow)?;
+            settlement.total_paid = settlement
+                .total_paid
+                .checked_add(amount)
+                .ok_or(VaultError::Overflow)?;
+
+            emit!(SettlementLossEvent {
+                vault: vault.key(),
+                epoch: settlement.epoch,
+                collateral_paid: amount,
+                total_assets: vault.total_assets,
+                price_per_share_before,
+                price_per_share_after: vault.price_per_share()?,
+            });
+        }
+
+        vault.open_positions = vault
+            .open_positions
+            .checked_sub(1)
+            .ok_or(VaultError::Overflow)?;
+
+        let exposure = position_exposure(
+            position.option_type,
+            position.notional,
+            position.strike_price,
+            vault.underlying_decimals,
+        )?;
+        let notional_value = notional_value(
+            position.notional,
+            position.strike_price,
+            vault.underlying_decimals,
+        )?;
+        ctx.accounts
+            .market_maker_account
+            .close_position(exposure, notional_value, amount)?;
+
+        emit!(SettlementPaidEvent {
+            vault: vault.key(),
+            position: position.key(),
+            recipient,
+            amount,
+            value,
+            epoch: settlement.epoch,
+        });
+
+        // Position account is closed by Anchor's close constraint
+        Ok(())
+    }
+
+    /// Exercise an ITM position on a physical-delivery vault (market maker signs)
+    /// Calls: the maker pays strike * notional in premium_mint and receives the
+    /// notional in underlying. Strike proceeds are credited to holders as premium
+    /// and distributed like premium.
+    /// Puts: the maker delivers the notional in underlying and receives strike *
+    /// notional from the collateral. Assigned underlying is tracked in
+    /// assigned_underlying and distributed pro-rata on withdrawal.
+    pub fn exercise_position(ctx: Context<ExercisePosition>) -> Result<()> {
+        let settlement = &mut ctx.accounts.epoch_settlement;
+        let position = &ctx.accounts.position;
+        let market_maker = ctx.accounts.market_maker.key();
 
-        // Verify the vault no longer exists
         require!(
-            ctx.accounts.vault_pda.data_is_empty(),
-            VaultError::VaultStillExists
+            ctx.accounts.vault.settlement_mode == SettlementMode::Physical,
+            VaultError::InvalidSettlementMode
         );
-
-        // Verify the vault PDA matches
+        require!(settlement.is_settled, VaultError::EpochNotSettled);
         require!(
-            ctx.accounts.vault_pda.key() == vault_pda,
-            VaultError::InvalidVaultPda
+            position.intrinsic_value(settlement)? > 0,
+            VaultError::PositionNotInTheMoney
+        );
+        let deadline = settlement
+            .settled_at
+            .checked_add(EXERCISE_WINDOW)
+            .ok_or(VaultError::Overflow)?;
+        require!(
+            Clock::get()?.unix_timestamp < deadline,
+            VaultError::ExerciseWindowClosed
         );
 
-        // SECURITY FIX H-1: Verify the token account is actually owned by this vault PDA
-        // This prevents closing arbitrary token accounts
+        let underlying_amount = position.notional;
+        let strike_amount = position.strike_value(settlement)?;
+        require!(strike_amount > 0, VaultError::ZeroAmount);
+
+        // Collateral leaving the vault
+        let collateral_amount = match position.option_type {
+            OptionType::Call => underlying_amount,
+            OptionType::Put => strike_amount,
+        };
         require!(
-            token_account.owner == vault_pda,
-            VaultError::InvalidTokenAccountOwner
+            ctx.accounts.vault.total_assets >= collateral_amount,
+            VaultError::InsufficientVaultBalance
         );
 
-        // Close the token account using vault PDA as signer
-        let seeds = &[b"vault".as_ref(), asset_id.as_bytes(), &[bump]];
+        // Maker pays: strike for calls, underlying for puts
+        // Credited net of any Token-2022 transfer fee
+        let maker_received = match position.option_type {
+            OptionType::Call => transfer_in(
+                &ctx.accounts.premium_token_program,
+                ctx.accounts.market_maker_premium_account.to_account_info(),
+                &mut ctx.accounts.vault_premium_account,
+                &ctx.accounts.premium_mint,
+                ctx.accounts.market_maker.to_account_info(),
+                strike_amount,
+                &[],
+            )?,
+            OptionType::Put => transfer_in(
+                &ctx.accounts.underlying_token_program,
+                ctx.accounts.market_maker_token_account.to_account_info(),
+                &mut ctx.accounts.vault_token_account,
+                &ctx.accounts.underlying_mint,
+                ctx.accounts.market_maker.to_account_info(),
+                underlying_amount,
+                &[],
+            )?,
+        };
+
+        // Vault delivers: underlying for calls, strike for puts
+        let (vault_from, maker_to, vault_mint, vault_program) = match position.option_type {
+            OptionType::Call => (
+                ctx.accounts.vault_token_account.to_account_info(),
+                ctx.accounts.market_maker_token_account.to_account_info(),
+                &ctx.accounts.underlying_mint,
+                &ctx.accounts.underlying_token_program,
+            ),
+            OptionType::Put => (
+                ctx.accounts.vault_premium_account.to_account_info(),
+                ctx.accounts.market_maker_premium_account.to_account_info(),
+                &ctx.accounts.premium_mint,
+                &ctx.accounts.premium_token_program,
+            ),
+        };
+        let vault = &mut ctx.accounts.vault;
+        let asset_id = vault.asset_id.as_bytes();
+        let seeds = &[b"vault", asset_id, &[vault.bump]];
         let signer_seeds = &[&seeds[..]];
 
-        token::close_account(CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            token::CloseAccount {
-                account: token_account.to_account_info(),
-                destination: authority.to_account_info(),
-                authority: ctx.accounts.vault_pda.to_account_info(),
-            },
-            signer_seeds,
-        ))?;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                vault_program.to_account_info(),
+                TransferChecked {
+                    from: vault_from,
+                    mint: vault_mint.to_account_info(),
+                    to: maker_to,
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            collateral_amount,
+            vault_mint.decimals,
+        )?;
 
-        msg!(
-            "Closed orphaned token account {} for vault {}",
-            token_account.key(),
-            asset_id
-        );
+        vault.total_assets = vault
+            .total_assets
+            .checked_sub(collateral_amount)
+            .ok_or(VaultError::Overflow)?;
+        match position.option_type {
+            OptionType::Call => vault.credit_premium(maker_received)?,
+            OptionType::Put => {
+                vault.assigned_underlying = vault
+                    .assigned_underlying
+                    .checked_add(maker_received)
+                    .ok_or(VaultError::Overflow)?;
+            }
+        }
+        vault.open_positions = vault
+            .open_positions
+            .checked_sub(1)
+            .ok_or(VaultError::Overflow)?;
+        settlement.total_paid = settlement
+            .total_paid
+            .checked_add(collateral_amount)
+            .ok_or(VaultError::Overflow)?;
+
+        let exposure = position_exposure(
+            position.option_type,
+            position.notional,
+            position.strike_price,
+            vault.underlying_decimals,
+        )?;
+        let notional_value = notional_value(
+            position.notional,
+            position.strike_price,
+            vault.underlying_decimals,
+        )?;
+        ctx.accounts.market_maker_account.close_position(
+            exposure,
+            notional_value,
+            collateral_amount,
+        )?;
+
+        emit!(PositionExercisedEvent {
+            vault: vault.key(),
+            position: position.key(),
+            market_maker,
+            epoch: settlement.epoch,
+            option_type: position.option_type,
+            underlying_amount,
+            strike_amount,
+            total_assets: vault.total_assets,
+            premium_balance_usdc: vault.premium_balance_usdc,
+        });
+
+        // Position account is closed by Anchor's close constraint
         Ok(())
     }
-}
-
-// ============================================================================
-// Account Structures
-// ============================================================================
 
-#[account]
-pub struct Vault {
-    pub authority: Pubkey,
-    pub asset_id: String,
-    pub underlying_mint: Pubkey,
-    pub share_mint: Pubkey,
-    pub vault_token_account: Pubkey,
-    // USDC premium escrow
-    pub premium_mint: Pubkey,
+    /// Create metadata for the share token (vNVDAx, etc.)
+    /// Only callable by vault authority since vault PDA is the mint authority
+    pub fn create_share_metadata(
+        ctx: Context<CreateShareMetadata>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let asset_id = vault.asset_id.as_bytes();
+        let seeds = &[b"vault", asset_id, &[vault.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        // Build the CreateMetadataAccountV3 instruction
+        let create_metadata_ix = mpl_token_metadata::instructions::CreateMetadataAccountV3 {
+            metadata: ctx.accounts.metadata.key(),
+            mint: ctx.accounts.share_mint.key(),
+            mint_authority: vault.key(),
+            payer: ctx.accounts.payer.key(),
+            update_authority: (vault.key(), true),
+            system_program: ctx.accounts.system_program.key(),
+            rent: Some(ctx.accounts.rent.key()),
+        };
+
+        let data_v2 = mpl_token_metadata::types::DataV2 {
+            name,
+            symbol,
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        };
+
+        let ix = create_metadata_ix.instruction(
+            mpl_token_metadata::instructions::CreateMetadataAccountV3InstructionArgs {
+                data: data_v2,
+                is_mutable: true,
+                collection_details: None,
+            },
+        );
+
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.metadata.to_account_info(),
+                ctx.accounts.share_mint.to_account_info(),
+                vault.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+
+        msg!(
+            "Created metadata for share token: {}",
+            ctx.accounts.share_mint.key()
+        );
+        Ok(())
+    }
+
+    /// SECURITY FIX M-4: Remove a market maker from the whitelist
+    /// Takes effect immediately; the maker can no longer open positions, while
+    /// its open positions still settle and its stats are kept
+    pub fn remove_market_maker(
+        ctx: Context<RemoveMarketMaker>,
+        market_maker: Pubkey,
+    ) -> Result<()> {
+        let maker = &mut ctx.accounts.market_maker_account;
+        require!(
+            maker.status == MarketMakerStatus::Active,
+            VaultError::NotWhitelisted
+        );
+
+        maker.status = MarketMakerStatus::Removed;
+
+        emit!(MarketMakerRemovedEvent {
+            vault: ctx.accounts.vault.key(),
+            market_maker,
+        });
+
+        Ok(())
+    }
+
+    /// Close a legacy VaultWhitelist, refunding its rent to the authority
+    /// Each listed maker is queued as an AddMarketMaker action with
+    /// notional_limit, so it trades only after the timelock like any new
+    /// maker. The PendingAction PDAs (ids from vault.next_action_id) are
+    /// passed in list order as remaining accounts; cancel_action drops the
+    /// action of a maker already re-added through queue_action
+    pub fn migrate_whitelist<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateWhitelist<'info>>,
+        notional_limit: u64,
+    ) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        queue_legacy_market_makers(
+            &mut ctx.accounts.vault,
+            vault_key,
+            &ctx.accounts.whitelist.market_makers,
+            notional_limit,
+            ctx.remaining_accounts,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )
+    }
+
+    /// SECURITY: Pause or unpause the vault (emergency control)
+    /// When paused, deposits and withdrawal requests are blocked
+    /// Pausing is instant for the admin or guardian; unpausing is an
+    /// AdminAction::Unpause through queue_action
+    pub fn set_pause(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(paused, VaultError::UseGovernanceQueue);
+        vault.is_paused = paused;
+
+        emit!(VaultPausedEvent {
+            vault: vault.key(),
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Queue an admin action, executable after vault.timelock_delay
+    /// Any number of actions may be queued at once, each in its own
+    /// PendingAction account keyed by vault.next_action_id
+    pub fn queue_action(ctx: Context<QueueAction>, action: AdminAction) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        let pending_action = ctx.accounts.vault.queue_action(
+            vault_key,
+            action,
+            Clock::get()?.unix_timestamp,
+            ctx.bumps.pending_action,
+        )?;
+
+        emit!(ActionQueuedEvent {
+            vault: vault_key,
+            action_id: pending_action.id,
+            action: pending_action.action.clone(),
+            executable_at: pending_action.executable_at,
+        });
+        ctx.accounts.pending_action.set_inner(pending_action);
+
+        Ok(())
+    }
+
+    /// Execute a queued admin action once its timelock has passed
+    /// AddMarketMaker requires the maker and its MarketMakerAccount;
+    /// CorporateAction requires the underlying mint
+    pub fn execute_action(ctx: Context<ExecuteAction>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let pending_action = &ctx.accounts.pending_action;
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp >= pending_action.executable_at,
+            VaultError::TimelockNotExpired
+        );
+
+        match pending_action.action.clone() {
+            AdminAction::AddMarketMaker {
+                market_maker,
+                notional_limit,
+            } => {
+                let maker_key = ctx
+                    .accounts
+                    .market_maker
+                    .as_ref()
+                    .ok_or(VaultError::MarketMakerAccountMissing)?
+                    .key();
+                require!(maker_key == market_maker, VaultError::InvalidCounterparty);
+                let maker = ctx
+                    .accounts
+                    .market_maker_account
+                    .as_mut()
+                    .ok_or(VaultError::MarketMakerAccountMissing)?;
+
+                // Counters are kept when a removed maker is re-added
+                if maker.vault == Pubkey::default() {
+                    maker.vault = vault.key();
+                    maker.market_maker = market_maker;
+                    maker.bump = ctx.bumps.market_maker_account.unwrap_or_default();
+                }
+                maker.status = MarketMakerStatus::Active;
+                maker.notional_limit = notional_limit;
+
+                emit!(MarketMakerAddedEvent {
+                    vault: vault.key(),
+                    market_maker,
+                    notional_limit,
+                });
+            }
+            AdminAction::Unpause => {
+                vault.is_paused = false;
+
+                emit!(VaultPausedEvent {
+                    vault: vault.key(),
+                    paused: false,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            AdminAction::ParamChange {
+                min_epoch_duration,
+                utilization_cap_bps,
+                max_total_assets,
+                max_deposit_per_user,
+            } => {
+                if let Some(duration) = min_epoch_duration {
+                    vault.min_epoch_duration = duration;
+                }
+                if let Some(cap) = utilization_cap_bps {
+                    vault.utilization_cap_bps = cap;
+                }
+                if let Some(cap) = max_total_assets {
+                    vault.max_total_assets = cap;
+                }
+                if let Some(cap) = max_deposit_per_user {
+                    vault.max_deposit_per_user = cap;
+                }
+
+                emit!(ParamChangeExecutedEvent {
+                    vault: vault.key(),
+                    new_min_epoch_duration: vault.min_epoch_duration,
+                    new_utilization_cap_bps: vault.utilization_cap_bps,
+                    new_max_total_assets: vault.max_total_assets,
+                    new_max_deposit_per_user: vault.max_deposit_per_user,
+                });
+            }
+            AdminAction::SetRoles {
+                keeper,
+                guardian,
+                settlement_operator,
+            } => {
+                vault.keeper = keeper;
+                vault.guardian = guardian;
+                vault.settlement_operator = settlement_operator;
+
+                emit!(RolesUpdatedEvent {
+                    vault: vault.key(),
+                    keeper,
+                    guardian,
+                    settlement_operator,
+                });
+            }
+            AdminAction::SetFeeConfig {
+                fee_recipient,
+                management_fee_bps,
+                performance_fee_bps,
+                deposit_fee_bps,
+                withdrawal_fee_bps,
+            } => {
+                vault.fee_recipient = fee_recipient;
+                vault.management_fee_bps = management_fee_bps;
+                vault.performance_fee_bps = performance_fee_bps;
+                vault.deposit_fee_bps = deposit_fee_bps;
+                vault.withdrawal_fee_bps = withdrawal_fee_bps;
+
+                emit!(FeeConfigUpdatedEvent {
+                    vault: vault.key(),
+                    fee_recipient,
+                    management_fee_bps,
+                    performance_fee_bps,
+                    deposit_fee_bps,
+                    withdrawal_fee_bps,
+                });
+            }
+            AdminAction::SetTimelockDelay { delay } => {
+                vault.timelock_delay = delay;
+            }
+            AdminAction::SetAttester { attester } => {
+                vault.attester = attester;
+            }
+            AdminAction::SetCompoundConfig {
+                swap_program,
+                compound_bps,
+            } => {
+                vault.swap_program = swap_program;
+                vault.compound_bps = compound_bps;
+
+                emit!(CompoundConfigUpdatedEvent {
+                    vault: vault.key(),
+                    swap_program,
+                    compound_bps,
+                });
+            }
+            AdminAction::SetOracleConfig {
+                price_feed_id,
+                max_price_staleness,
+                max_confidence_bps,
+            } => {
+                // Cannot be changed while the current epoch has open exposure
+                require!(
+                    vault.epoch_notional_exposed == 0,
+                    VaultError::ExposureOutstanding
+                );
+                vault.price_feed_id = price_feed_id;
+                vault.max_price_staleness = max_price_staleness;
+                vault.max_confidence_bps = max_confidence_bps;
+
+                emit!(OracleConfigUpdatedEvent {
+                    vault: vault.key(),
+                    price_feed_id,
+                    max_price_staleness,
+                    max_confidence_bps,
+                });
+            }
+            AdminAction::SetSettlementMode { mode } => {
+                // Cannot be changed while option positions are open
+                require!(vault.open_positions == 0, VaultError::OpenPositions);
+                vault.settlement_mode = mode;
+
+                emit!(SettlementModeUpdatedEvent {
+                    vault: vault.key(),
+                    mode,
+                });
+            }
+            AdminAction::SetDepositQueue { enabled } => {
+                vault.deposit_queue_enabled = enabled;
+
+                emit!(DepositQueueUpdatedEvent {
+                    vault: vault.key(),
+                    enabled,
+                });
+            }
+            AdminAction::TransferAuthority { new_authority } => {
+                // The new key proves control by calling accept_authority
+                vault.pending_authority = new_authority;
+                vault.authority_transfer_unlock_time = clock.unix_timestamp;
+
+                emit!(AuthorityProposedEvent {
+                    vault: vault.key(),
+                    current_authority: vault.authority,
+                    pending_authority: new_authority,
+                    unlock_time: clock.unix_timestamp,
+                });
+            }
+            AdminAction::CorporateAction { new_multiplier } => {
+                // Applied once the mint's multiplier has changed to match
+                let underlying_mint = ctx
+                    .accounts
+                    .underlying_mint
+                    .as_ref()
+                    .ok_or(VaultError::UnderlyingMintMissing)?;
+                require!(
+                    new_multiplier == mint_ui_multiplier(underlying_mint, clock.unix_timestamp)?,
+                    VaultError::MultiplierMismatch
+                );
+
+                // Raw balances, total_assets, exposure and price-per-share are
+                // unchanged; strikes and oracle prices are converted at the new
+                // multiplier from now on, and recorded positions keep their
+                // raw-token strikes
+                let old_multiplier = vault.underlying_multiplier;
+                vault.underlying_multiplier = new_multiplier;
+                vault.pending_underlying_multiplier = 0;
+                vault.corporate_action_unlock_time = 0;
+
+                emit!(CorporateActionAppliedEvent {
+                    vault: vault.key(),
+                    epoch: vault.epoch,
+                    old_multiplier,
+                    new_multiplier,
+                    open_positions: vault.open_positions,
+                });
+            }
+        }
+
+        emit!(ActionExecutedEvent {
+            vault: vault.key(),
+            action_id: pending_action.id,
+            action: pending_action.action.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a queued admin action and reclaim its rent
+    pub fn cancel_action(ctx: Context<CancelAction>) -> Result<()> {
+        if let AdminAction::CorporateAction { .. } = ctx.accounts.pending_action.action {
+            let vault = &mut ctx.accounts.vault;
+            vault.pending_underlying_multiplier = 0;
+            vault.corporate_action_unlock_time = 0;
+        }
+
+        emit!(ActionCancelledEvent {
+            vault: ctx.accounts.vault.key(),
+            action_id: ctx.accounts.pending_action.id,
+        });
+
+        Ok(())
+    }
+
+    /// Lower either deposit cap immediately (authority only)
+    /// A cap of 0 leaves that cap unchanged; raising or removing a cap goes
+    /// through an AdminAction::ParamChange
+    pub fn tighten_deposit_caps(
+        ctx: Context<SetParam>,
+        max_total_assets: u64,
+        max_deposit_per_user: u64,
+    ) -> Result<()> {
+        require!(
+            max_total_assets > 0 || max_deposit_per_user > 0,
+            VaultError::InvalidParameter
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        vault.max_total_assets = tightened_cap(vault.max_total_assets, max_total_assets)?;
+        vault.max_deposit_per_user =
+            tightened_cap(vault.max_deposit_per_user, max_deposit_per_user)?;
+
+        emit!(ParamChangeExecutedEvent {
+            vault: vault.key(),
+            new_min_epoch_duration: vault.min_epoch_duration,
+            new_utilization_cap_bps: vault.utilization_cap_bps,
+            new_max_total_assets: vault.max_total_assets,
+            new_max_deposit_per_user: vault.max_deposit_per_user,
+        });
+
+        Ok(())
+    }
+
+    /// Issue or renew a wallet's attestation (attester signs)
+    pub fn issue_attestation(
+        ctx: Context<IssueAttestation>,
+        wallet: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            VaultError::InvalidParameter
+        );
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.vault = ctx.accounts.vault.key();
+        attestation.wallet = wallet;
+        attestation.attester = ctx.accounts.attester.key();
+        attestation.expires_at = expires_at;
+        attestation.bump = ctx.bumps.attestation;
+
+        emit!(AttestationIssuedEvent {
+            vault: attestation.vault,
+            wallet,
+            attester: attestation.attester,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a wallet's attestation and reclaim its rent (attester signs)
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>, wallet: Pubkey) -> Result<()> {
+        emit!(AttestationRevokedEvent {
+            vault: ctx.accounts.vault.key(),
+            wallet,
+        });
+
+        Ok(())
+    }
+
+    /// Accept an authority transfer executed through the governance queue
+    /// Keeper, guardian, settlement operator and fee recipient are kept; the
+    /// new authority changes them through the governance queue
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp >= vault.authority_transfer_unlock_time,
+            VaultError::TimelockNotExpired
+        );
+
+        let old_authority = vault.authority;
+        let new_authority = vault.pending_authority;
+        vault.authority = new_authority;
+        vault.pending_authority = Pubkey::default();
+        vault.authority_transfer_unlock_time = 0;
+
+        emit!(AuthorityTransferredEvent {
+            vault: vault.key(),
+            old_authority,
+            new_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw an executed authority transfer before it is accepted
+    pub fn cancel_authority_transfer(ctx: Context<SetParam>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+
+        vault.pending_authority = Pubkey::default();
+        vault.authority_transfer_unlock_time = 0;
+
+        Ok(())
+    }
+
+    /// DEPRECATED: Direct parameter changes now require timelock
+    /// Kept for backwards compatibility but will fail
+    pub fn set_min_epoch_duration(_ctx: Context<SetParam>, _duration: i64) -> Result<()> {
+        Err(VaultError::UseTimelockForParamChange.into())
+    }
+
+    /// DEPRECATED: Direct parameter changes now require timelock
+    /// Kept for backwards compatibility but will fail
+    pub fn set_utilization_cap(_ctx: Context<SetParam>, _cap_bps: u16) -> Result<()> {
+        Err(VaultError::UseTimelockForParamChange.into())
+    }
+
+    /// Record a dividend paid by the issuer into one of the vault's token accounts
+    /// Only the balance not already tracked by the vault can be recorded.
+    /// Reinvest credits collateral dividends to total_assets (and underlying
+    /// dividends in put vaults to assigned_underlying); Distribute credits
+    /// premium_mint dividends to the premium-per-share index for claim_premium
+    /// Authority only
+    pub fn record_dividend(
+        ctx: Context<RecordDividend>,
+        amount: u64,
+        treatment: DividendTreatment,
+    ) -> Result<()> {
+        require!(amount > 0, VaultError::ZeroAmount);
+
+        let vault = &mut ctx.accounts.vault;
+        let dividend_account = &ctx.accounts.dividend_account;
+        let is_underlying = dividend_account.key() == vault.vault_token_account;
+
+        let tracked = if is_underlying {
+            vault.tracked_underlying_balance()?
+        } else {
+            vault.tracked_premium_balance()?
+        };
+        let unaccounted = dividend_account.amount.saturating_sub(tracked);
+        require!(
+            amount <= unaccounted,
+            VaultError::DividendExceedsUnaccountedBalance
+        );
+
+        match (treatment, is_underlying, vault.strategy) {
+            (DividendTreatment::Reinvest, true, Strategy::CoveredCall)
+            | (DividendTreatment::Reinvest, false, Strategy::CashSecuredPut) => {
+                vault.total_assets = vault
+                    .total_assets
+                    .checked_add(amount)
+                    .ok_or(VaultError::Overflow)?;
+            }
+            (DividendTreatment::Reinvest, true, Strategy::CashSecuredPut) => {
+                vault.assigned_underlying = vault
+                    .assigned_underlying
+                    .checked_add(amount)
+                    .ok_or(VaultError::Overflow)?;
+            }
+            (DividendTreatment::Distribute, false, _) => {
+                vault.credit_premium(amount)?;
+            }
+            _ => return Err(VaultError::InvalidDividendTreatment.into()),
+        }
+
+        emit!(DividendRecordedEvent {
+            vault: vault.key(),
+            epoch: vault.epoch,
+            mint: dividend_account.mint,
+            amount,
+            treatment,
+            total_assets: vault.total_assets,
+            premium_per_share_index: vault.premium_per_share_index,
+        });
+
+        Ok(())
+    }
+
+    /// Reconcile premium_balance_usdc state with actual token account balance
+    /// Use this to fix state drift caused by manual token movements or bugs
+    /// Authority only
+    pub fn reconcile_premium_balance(ctx: Context<ReconcilePremiumBalance>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let old_balance = vault.premium_balance_usdc;
+        // Premium received this epoch is credited at the roll, not here
+        let actual_balance = vault.premium_balance_in(ctx.accounts.vault_premium_account.amount);
+
+        vault.premium_balance_usdc = actual_balance;
+
+        msg!(
+            "Reconciled premium balance: {} -> {}",
+            old_balance,
+            actual_balance
+        );
+
+        emit!(PremiumBalanceReconciledEvent {
+            vault: vault.key(),
+            old_balance,
+            new_balance: actual_balance,
+        });
+
+        Ok(())
+    }
+
+    /// Close a vault and recover rent (authority only)
+    /// Vault must be empty (no assets, shares, or pending withdrawals)
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+
+        // Safety checks - vault must be completely empty
+        require!(vault.total_assets == 0, VaultError::VaultNotEmpty);
+        require!(vault.total_shares == 0, VaultError::VaultNotEmpty);
+        require!(vault.pending_withdrawals == 0, VaultError::VaultNotEmpty);
+        require!(vault.pending_deposits == 0, VaultError::VaultNotEmpty);
+        require!(vault.epoch_notional_exposed == 0, VaultError::VaultNotEmpty);
+        require!(vault.open_positions == 0, VaultError::OpenPositions);
+
+        // Account will be closed automatically by Anchor's close constraint
+        Ok(())
+    }
+
+    /// Upgrade a pre-versioning (version 0) Vault account in place (authority only)
+    /// Reallocs to the current layout, keeping balances, PDAs and token accounts.
+    /// New fields take initialize_vault defaults; roles start as the authority
+    /// and the legacy premium balance is credited to the premium-per-share index.
+    /// Legacy shares have no transfer hook to keep premium checkpoints in sync,
+    /// so the vault gets a new hooked share mint; holders swap with
+    /// convert_legacy_shares, which pays them the legacy premium.
+    /// Legacy withdrawal requests must be processed and the epoch rolled with
+    /// no exposure first. A legacy whitelist is closed and its makers queued
+    /// as in migrate_whitelist, with maker_notional_limit.
+    pub fn migrate_vault<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateVault<'info>>,
+        asset_id: String,
+        maker_notional_limit: u64,
+    ) -> Result<()> {
+        let vault_info = ctx.accounts.vault.to_account_info();
+        require!(
+            vault_info.owner == ctx.program_id,
+            VaultError::InvalidVaultLayout
+        );
+
+        let old_len = vault_info.data_len();
+        let new_len = 8 + Vault::INIT_SPACE;
+        let legacy = {
+            let data = vault_info.try_borrow_data()?;
+            match stored_vault_version(&data)? {
+                0 => {}
+                CURRENT_VAULT_VERSION => return err!(VaultError::VaultAlreadyMigrated),
+                _ => return err!(VaultError::UnsupportedVaultVersion),
+            }
+            LegacyVaultV0::deserialize(&mut &data[8..])
+                .map_err(|_| VaultError::InvalidVaultLayout)?
+        };
+
+        require!(legacy.asset_id == asset_id, VaultError::InvalidVaultPda);
+        require!(
+            legacy.authority == ctx.accounts.authority.key(),
+            VaultError::Unauthorized
+        );
+        require!(
+            legacy.underlying_mint == ctx.accounts.underlying_mint.key()
+                && legacy.premium_mint == ctx.accounts.premium_mint.key(),
+            VaultError::InvalidVaultLayout
+        );
+        require!(
+            legacy.pending_withdrawals == 0,
+            VaultError::PendingWithdrawals
+        );
+        // Legacy fills have no EpochSettlement, so the epoch could not roll
+        require!(
+            legacy.epoch_notional_exposed == 0,
+            VaultError::ExposureOutstanding
+        );
+
+        require!(
+            legacy.share_mint == ctx.accounts.legacy_share_mint.key(),
+            VaultError::InvalidVaultLayout
+        );
+
+        let mut vault = legacy.into_vault(
+            ctx.accounts.share_mint.key(),
+            ctx.accounts.underlying_mint.decimals,
+            mint_ui_multiplier(&ctx.accounts.underlying_mint, Clock::get()?.unix_timestamp)?,
+            *ctx.accounts.underlying_mint.to_account_info().owner,
+            *ctx.accounts.premium_mint.to_account_info().owner,
+        )?;
+        if let Some(whitelist) = &ctx.accounts.whitelist {
+            queue_legacy_market_makers(
+                &mut vault,
+                vault_info.key(),
+                &whitelist.market_makers,
+                maker_notional_limit,
+                ctx.remaining_accounts,
+                &ctx.accounts.authority,
+                &ctx.accounts.system_program,
+            )?;
+        }
+
+        // Top up rent for the larger account, then grow it in place
+        let rent_due = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(vault_info.lamports());
+        if rent_due > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: vault_info.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        vault_info.resize(new_len)?;
+        vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+        init_share_hook_metas(&ctx.accounts.extra_account_meta_list, &vault_info.key())?;
+
+        emit!(VaultMigratedEvent {
+            vault: vault_info.key(),
+            from_version: 0,
+            to_version: CURRENT_VAULT_VERSION,
+            old_len: old_len as u32,
+            new_len: new_len as u32,
+        });
+
+        Ok(())
+    }
+
+    /// Swap legacy shares of a migrated vault 1:1 for hooked shares
+    /// The new shares' checkpoint is credited with the premium the legacy
+    /// shares earned since migration; total_shares is unchanged
+    pub fn convert_legacy_shares(ctx: Context<ConvertLegacyShares>, shares: u64) -> Result<()> {
+        require!(shares > 0, VaultError::ZeroAmount);
+
+        let vault = &ctx.accounts.vault;
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.legacy_token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.legacy_share_mint.to_account_info(),
+                    from: ctx.accounts.user_legacy_share_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        let balance = ctx.accounts.user_share_account.amount;
+        sync_premium_checkpoint(
+            &mut ctx.accounts.premium_checkpoint,
+            vault,
+            ctx.accounts.user_share_account.key(),
+            balance,
+            balance.checked_add(shares).ok_or(VaultError::Overflow)?,
+            ctx.bumps.premium_checkpoint,
+        )?;
+        let accrued_before = ctx.accounts.premium_checkpoint.accrued;
+        ctx.accounts
+            .premium_checkpoint
+            .accrue_since(vault.premium_per_share_index, 0, shares)?;
+
+        let asset_id = vault.asset_id.as_bytes();
+        let seeds = &[b"vault", asset_id, &[vault.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.user_share_account.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            shares,
+        )?;
+
+        emit!(LegacySharesConvertedEvent {
+            vault: vault.key(),
+            user: ctx.accounts.user.key(),
+            shares,
+            premium_accrued: ctx.accounts.premium_checkpoint.accrued - accrued_before,
+        });
+
+        Ok(())
+    }
+
+    /// Force close a vault account (bypasses deserialization)
+    /// USE WITH CAUTION: Only for recovering from incompatible account structures
+    /// Prefer migrate_vault, which keeps the vault's token accounts usable
+    /// SECURITY FIX C-2: Now verifies caller is the stored vault authority
+    pub fn force_close_vault(ctx: Context<ForceCloseVault>, asset_id: String) -> Result<()> {
+        let vault_account = &ctx.accounts.vault;
+        let authority = &ctx.accounts.authority;
+
+        // Verify the PDA matches what we expect
+        let (expected_pda, _bump) =
+            Pubkey::find_program_address(&[b"vault", asset_id.as_bytes()], ctx.program_id);
+        require!(
+            vault_account.key() == expected_pda,
+            VaultError::InvalidVaultPda
+        );
+
+        // SECURITY FIX C-2: Verify caller is the vault authority
+        // Authority is stored at bytes 8-40 (after 8-byte discriminator)
+        let vault_data = vault_account.try_borrow_data()?;
+        require!(vault_data.len() >= 40, VaultError::InvalidVaultPda);
+
+        let stored_authority =
+            Pubkey::try_from(&vault_data[8..40]).map_err(|_| VaultError::InvalidVaultPda)?;
+        require!(
+            stored_authority == authority.key(),
+            VaultError::UnauthorizedForceClose
+        );
+        drop(vault_data); // Release borrow before modifying
+
+        // Transfer all lamports to authority
+        let vault_lamports = vault_account.lamports();
+        **vault_account.try_borrow_mut_lamports()? = 0;
+        **authority.try_borrow_mut_lamports()? = authority
+            .lamports()
+            .checked_add(vault_lamports)
+            .ok_or(VaultError::Overflow)?;
+
+        // Zero out the account data
+        vault_account.try_borrow_mut_data()?.fill(0);
+
+        msg!(
+            "Force closed vault {} - returned {} lamports",
+            asset_id,
+            vault_lamports
+        );
+        Ok(())
+    }
+
+    /// Close an orphaned token account that was owned by a vault PDA
+    /// This is used to clean up old share escrows, vault token accounts, etc.
+    /// after a vault has been force-closed, enabling reuse of asset IDs.
+    ///
+    /// The caller must provide the correct asset_id that was used to derive the vault PDA.
+    /// The vault PDA must NOT exist anymore (force-closed).
+    pub fn close_orphaned_token_account(
+        ctx: Context<CloseOrphanedTokenAccount>,
+        asset_id: String,
+    ) -> Result<()> {
+        let token_account = &ctx.accounts.token_account;
+        let authority = &ctx.accounts.authority;
+
+        // Derive vault PDA - this is the owner of the orphaned token account
+        let (vault_pda, bump) =
+            Pubkey::find_program_address(&[b"vault", asset_id.as_bytes()], ctx.program_id);
+
+        // Verify the vault no longer exists
+        require!(
+            ctx.accounts.vault_pda.data_is_empty(),
+            VaultError::VaultStillExists
+        );
+
+        // Verify the vault PDA matches
+        require!(
+            ctx.accounts.vault_pda.key() == vault_pda,
+            VaultError::InvalidVaultPda
+        );
+
+        // SECURITY FIX H-1: Verify the token account is actually owned by this vault PDA
+        // This prevents closing arbitrary token accounts
+        require!(
+            token_account.owner == vault_pda,
+            VaultError::InvalidTokenAccountOwner
+        );
+
+        // Close the token account using vault PDA as signer
+        let seeds = &[b"vault".as_ref(), asset_id.as_bytes(), &[bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: token_account.to_account_info(),
+                destination: authority.to_account_info(),
+                authority: ctx.accounts.vault_pda.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        msg!(
+            "Closed orphaned token account {} for vault {}",
+            token_account.key(),
+            asset_id
+        );
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Market Makers
+// ============================================================================
+
+/// Queue an AddMarketMaker action with notional_limit for each legacy
+/// whitelisted maker; pending_actions are the actions' PDAs in list order
+fn queue_legacy_market_makers<'info>(
+    vault: &mut Vault,
+    vault_key: Pubkey,
+    makers: &[Pubkey],
+    notional_limit: u64,
+    pending_actions: &'info [AccountInfo<'info>],
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    require!(
+        pending_actions.len() == makers.len(),
+        VaultError::InvalidPendingActionAccount
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let space = 8 + PendingAction::INIT_SPACE;
+    let rent = Rent::get()?.minimum_balance(space);
+    for (market_maker, action_info) in makers.iter().zip(pending_actions) {
+        let id = vault.next_action_id.to_le_bytes();
+        let (address, bump) =
+            Pubkey::find_program_address(&[b"pending_action", vault_key.as_ref(), &id], &crate::ID);
+        require_keys_eq!(
+            action_info.key(),
+            address,
+            VaultError::InvalidPendingActionAccount
+        );
+
+        // Fund, allocate and assign (works even if the PDA was pre-funded)
+        let signer_seeds: &[&[&[u8]]] = &[&[b"pending_action", vault_key.as_ref(), &id, &[bump]]];
+        let top_up = rent.saturating_sub(action_info.lamports());
+        if top_up > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: payer.to_account_info(),
+                        to: action_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        anchor_lang::system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Allocate {
+                    account_to_allocate: action_info.clone(),
+                },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        anchor_lang::system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Assign {
+                    account_to_assign: action_info.clone(),
+                },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )?;
+
+        let pending_action = vault.queue_action(
+            vault_key,
+            AdminAction::AddMarketMaker {
+                market_maker: *market_maker,
+                notional_limit,
+            },
+            now,
+            bump,
+        )?;
+        pending_action.try_serialize(&mut &mut action_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ActionQueuedEvent {
+            vault: vault_key,
+            action_id: pending_action.id,
+            action: pending_action.action,
+            executable_at: pending_action.executable_at,
+        });
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Positions
+// ============================================================================
+
+/// Terms of an RFQ fill
+struct FillTerms {
+    market_maker: Pubkey,
+    notional_tokens: u64,
+    premium: u64,
+    strike_price: u64,
+}
+
+/// Collateral a position can consume: the notional for calls, the strike
+/// value of the notional for puts
+fn position_exposure(
+    option_type: OptionType,
+    notional: u64,
+    strike_price: u64,
+    underlying_decimals: u8,
+) -> Result<u64> {
+    match option_type {
+        OptionType::Call => Ok(notional),
+        OptionType::Put => notional_value(notional, strike_price, underlying_decimals),
+    }
+}
+
+/// Strike value of a notional in premium_mint tokens
+fn notional_value(notional: u64, strike_price: u64, underlying_decimals: u8) -> Result<u64> {
+    let underlying_unit = 10u128
+        .checked_pow(underlying_decimals as u32)
+        .ok_or(VaultError::Overflow)?;
+    let value = (notional as u128)
+        .checked_mul(strike_price as u128)
+        .ok_or(VaultError::Overflow)?
+        .checked_div(underlying_unit)
+        .ok_or(VaultError::Overflow)?;
+    u64::try_from(value).map_err(|_| VaultError::Overflow.into())
+}
+
+/// Open an OptionPosition for a fill and update the epoch's and the maker's
+/// exposure tracking
+fn open_position(
+    vault: &mut Account<Vault>,
+    maker: &mut Account<MarketMakerAccount>,
+    settlement: &mut Account<EpochSettlement>,
+    settlement_bump: u8,
+    position: &mut Account<OptionPosition>,
+    position_bump: u8,
+    terms: FillTerms,
+) -> Result<()> {
+    let FillTerms {
+        market_maker,
+        notional_tokens,
+        premium,
+        strike_price,
+    } = terms;
+
+    require!(notional_tokens > 0, VaultError::ZeroAmount);
+    require!(strike_price > 0, VaultError::InvalidParameter);
+    require!(
+        maker.market_maker == market_maker && maker.status == MarketMakerStatus::Active,
+        VaultError::NotWhitelisted
+    );
+
+    // First fill of the epoch initializes the settlement record
+    if settlement.vault == Pubkey::default() {
+        settlement.vault = vault.key();
+        settlement.epoch = vault.epoch;
+        settlement.bump = settlement_bump;
+    }
+    require!(!settlement.is_settled, VaultError::EpochAlreadySettled);
+
+    // Strikes are quoted per UI token; positions record them per raw token so
+    // later corporate actions do not change their value
+    let strike_price = vault.to_raw_price(strike_price)?;
+
+    let option_type = match vault.strategy {
+        Strategy::CoveredCall => OptionType::Call,
+        Strategy::CashSecuredPut => OptionType::Put,
+    };
+    let exposure = position_exposure(
+        option_type,
+        notional_tokens,
+        strike_price,
+        vault.underlying_decimals,
+    )?;
+
+    // Calculate max exposure: TVL * utilization_cap_bps / 10000
+    let max_exposure = (vault.total_assets as u128)
+        .checked_mul(vault.utilization_cap_bps as u128)
+        .ok_or(VaultError::Overflow)?
+        .checked_div(10000)
+        .ok_or(VaultError::Overflow)? as u64;
+
+    let new_exposure = vault
+        .epoch_notional_exposed
+        .checked_add(exposure)
+        .ok_or(VaultError::Overflow)?;
+
+    require!(
+        new_exposure <= max_exposure,
+        VaultError::ExceedsUtilizationCap
+    );
+
+    let maker_exposure = maker
+        .outstanding_exposure
+        .checked_add(exposure)
+        .ok_or(VaultError::Overflow)?;
+    require!(
+        maker_exposure <= maker.notional_limit,
+        VaultError::ExceedsMarketMakerLimit
+    );
+    maker.outstanding_exposure = maker_exposure;
+
+    // Collateral caps the notional a maker can be allocated
+    maker.outstanding_notional_value = maker
+        .outstanding_notional_value
+        .checked_add(notional_value(
+            notional_tokens,
+            strike_price,
+            vault.underlying_decimals,
+        )?)
+        .ok_or(VaultError::Overflow)?;
+    maker.check_collateral()?;
+
+    position.vault = vault.key();
+    position.epoch = vault.epoch;
+    position.index = settlement.position_count;
+    position.counterparty = market_maker;
+    position.option_type = option_type;
+    position.strike_price = strike_price;
+    position.expiry = vault
+        .last_roll_timestamp
+        .checked_add(vault.min_epoch_duration)
+        .ok_or(VaultError::Overflow)?;
+    position.notional = notional_tokens;
+    position.premium = premium;
+    position.bump = position_bump;
+
+    settlement.position_count = settlement
+        .position_count
+        .checked_add(1)
+        .ok_or(VaultError::Overflow)?;
+    settlement.total_premium = settlement
+        .total_premium
+        .checked_add(premium)
+        .ok_or(VaultError::Overflow)?;
+    vault.open_positions = vault
+        .open_positions
+        .checked_add(1)
+        .ok_or(VaultError::Overflow)?;
+
+    // Update epoch tracking
+    vault.epoch_notional_exposed = new_exposure;
+    vault.epoch_premium_earned = vault
+        .epoch_premium_earned
+        .checked_add(premium)
+        .ok_or(VaultError::Overflow)?;
+
+    // Calculate running average premium rate in basis points
+    if vault.epoch_notional_exposed > 0 {
+        vault.epoch_premium_per_token_bps = ((vault.epoch_premium_earned as u128)
+            .checked_mul(10000)
+            .ok_or(VaultError::Overflow)?
+            .checked_div(vault.epoch_notional_exposed as u128)
+            .ok_or(VaultError::Overflow)?) as u32;
+    }
+
+    emit!(NotionalExposureEvent {
+        vault: vault.key(),
+        epoch: vault.epoch,
+        position: position.key(),
+        market_maker,
+        notional_tokens,
+        premium,
+        strike_price,
+        total_notional_this_epoch: vault.epoch_notional_exposed,
+        total_premium_this_epoch: vault.epoch_premium_earned,
+        avg_premium_bps: vault.epoch_premium_per_token_bps,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// Token Transfers
+// ============================================================================
+
+/// Token-2022 mint extensions the vault can custody. Transfer hooks,
+/// non-transferable mints, default account state and confidential
+/// mint/burn would break vault transfers and are rejected, as are permanent
+/// delegates, which could move or burn the vault's custody at will
+const SUPPORTED_MINT_EXTENSIONS: [ExtensionType; 13] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MintCloseAuthority,
+    ExtensionType::ConfidentialTransferMint,
+    ExtensionType::ConfidentialTransferFeeConfig,
+    ExtensionType::InterestBearingConfig,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::GroupPointer,
+    ExtensionType::TokenGroup,
+    ExtensionType::GroupMemberPointer,
+    ExtensionType::TokenGroupMember,
+    ExtensionType::ScaledUiAmount,
+    ExtensionType::Pausable,
+];
+
+/// Reject Token-2022 mints carrying extensions outside SUPPORTED_MINT_EXTENSIONS
+fn validate_mint_extensions(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        require!(
+            SUPPORTED_MINT_EXTENSIONS.contains(&extension),
+            VaultError::UnsupportedMintExtension
+        );
+    }
+    Ok(())
+}
+
+/// UI tokens per raw token (PRICE_PER_SHARE_SCALE) the mint applies at
+/// unix_timestamp: its ScaledUiAmount multiplier, or 1 without the extension
+fn mint_ui_multiplier(mint: &InterfaceAccount<Mint>, unix_timestamp: i64) -> Result<u64> {
+    let unscaled = PRICE_PER_SHARE_SCALE as u64;
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(unscaled);
+    }
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(config) = state.get_extension::<ScaledUiAmountConfig>() else {
+        return Ok(unscaled);
+    };
+    let multiplier = if unix_timestamp >= i64::from(config.new_multiplier_effective_timestamp) {
+        f64::from(config.new_multiplier)
+    } else {
+        f64::from(config.multiplier)
+    };
+    let scaled = (multiplier * PRICE_PER_SHARE_SCALE as f64).round();
+    require!(
+        scaled >= 1.0 && scaled <= u64::MAX as f64,
+        VaultError::InvalidParameter
+    );
+    Ok(scaled as u64)
+}
+
+/// Require the vault's multiplier to match the underlying mint's, so strikes
+/// and oracle prices are never converted at a stale scale
+fn check_underlying_multiplier(
+    vault: &Vault,
+    underlying_mint: &InterfaceAccount<Mint>,
+) -> Result<()> {
+    let mint_multiplier = mint_ui_multiplier(underlying_mint, Clock::get()?.unix_timestamp)?;
+    require!(
+        vault.underlying_multiplier == mint_multiplier,
+        VaultError::MultiplierMismatch
+    );
+    Ok(())
+}
+
+/// transfer_checked into a vault account, returning the amount credited
+/// (Token-2022 transfer fees are withheld from the recipient)
+fn transfer_in<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: AccountInfo<'info>,
+    to: &mut InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    authority: AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    let balance_before = to.amount;
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from,
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority,
+            },
+            signer_seeds,
+        ),
+        amount,
+        mint.decimals,
+    )?;
+    to.reload()?;
+    to.amount
+        .checked_sub(balance_before)
+        .ok_or_else(|| VaultError::Overflow.into())
+}
+
+// ============================================================================
+// Deposit Caps
+// ============================================================================
+
+/// Cap after tighten_deposit_caps: new of 0 keeps current; otherwise new must
+/// not exceed current (0 is uncapped, so any cap is tighter than none)
+fn tightened_cap(current: u64, new: u64) -> Result<u64> {
+    if new == 0 {
+        return Ok(current);
+    }
+    require!(
+        current == 0 || new <= current,
+        VaultError::UseGovernanceQueue
+    );
+    Ok(new)
+}
+
+// ============================================================================
+// Fees
+// ============================================================================
+
+/// amount * bps / 10000, rounded down
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let value = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(VaultError::Overflow)?
+        .checked_div(10000)
+        .ok_or(VaultError::Overflow)?;
+    u64::try_from(value).map_err(|_| VaultError::Overflow.into())
+}
+
+// ============================================================================
+// Premium Accrual
+// ============================================================================
+
+/// Accrue premium credited since the last checkpoint and record the new balance
+/// (lazily initializing the checkpoint)
+fn sync_premium_checkpoint(
+    checkpoint: &mut Account<PremiumCheckpoint>,
+    vault: &Account<Vault>,
+    share_account: Pubkey,
+    balance: u64,
+    new_balance: u64,
+    bump: u8,
+) -> Result<()> {
+    // Lazily initialize on first use (init_if_needed)
+    if checkpoint.vault == Pubkey::default() {
+        checkpoint.vault = vault.key();
+        checkpoint.share_account = share_account;
+        checkpoint.premium_index = vault.premium_per_share_index;
+        checkpoint.bump = bump;
+    }
+
+    checkpoint.accrue(vault.premium_per_share_index, balance, new_balance)
+}
+
+/// Sync the fee recipient's checkpoint before fee shares are minted to it
+fn sync_fee_checkpoint(
+    checkpoint: Option<&mut Account<PremiumCheckpoint>>,
+    vault: &Account<Vault>,
+    fee_share_account: &InterfaceAccount<TokenAccount>,
+    fee_shares: u64,
+) -> Result<()> {
+    let checkpoint = checkpoint.ok_or(VaultError::PremiumCheckpointMissing)?;
+    require!(
+        checkpoint.vault == vault.key() && checkpoint.share_account == fee_share_account.key(),
+        VaultError::PremiumCheckpointMissing
+    );
+    let balance = fee_share_account.amount;
+    let new_balance = balance
+        .checked_add(fee_shares)
+        .ok_or(VaultError::Overflow)?;
+    checkpoint.accrue(vault.premium_per_share_index, balance, new_balance)
+}
+
+/// Checkpoint updates for a share transfer of amount, given the balances
+/// after the transfer (the hook runs once Token-2022 has moved the shares)
+fn move_premium_accrual(
+    premium_index: u128,
+    source: &mut PremiumCheckpoint,
+    source_balance: u64,
+    destination: &mut PremiumCheckpoint,
+    destination_balance: u64,
+    amount: u64,
+) -> Result<()> {
+    source.accrue(
+        premium_index,
+        source_balance
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?,
+        source_balance,
+    )?;
+    destination.accrue(
+        premium_index,
+        destination_balance
+            .checked_sub(amount)
+            .ok_or(VaultError::Overflow)?,
+        destination_balance,
+    )
+}
+
+// ============================================================================
+// Share Transfer Hook
+// ============================================================================
+
+/// Extra accounts of the share mint's transfer hook, following the Execute
+/// accounts (0 source, 1 mint, 2 destination, 3 owner, 4 this list):
+/// 5 vault, 6 source checkpoint, 7 destination checkpoint,
+/// 8 attestation of the destination's owner
+const SHARE_HOOK_EXTRA_ACCOUNTS: usize = 4;
+
+fn share_hook_extra_metas(vault: &Pubkey) -> Result<Vec<ExtraAccountMeta>> {
+    let checkpoint_of = |share_account_index: u8| {
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: b"premium_checkpoint".to_vec(),
+                },
+                Seed::AccountKey { index: 5 },
+                Seed::AccountKey {
+                    index: share_account_index,
+                },
+            ],
+            false,
+            true,
+        )
+    };
+    // Owner is at offset 32 of the destination token account
+    let destination_attestation = ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: b"attestation".to_vec(),
+            },
+            Seed::AccountKey { index: 5 },
+            Seed::AccountData {
+                account_index: 2,
+                data_index: 32,
+                length: 32,
+            },
+        ],
+        false,
+        false,
+    )?;
+    Ok(vec![
+        ExtraAccountMeta::new_with_pubkey(vault, false, false)?,
+        checkpoint_of(0)?,
+        checkpoint_of(2)?,
+        destination_attestation,
+    ])
+}
+
+/// Write the share mint's ExtraAccountMetaList
+fn init_share_hook_metas(meta_list: &AccountInfo, vault: &Pubkey) -> Result<()> {
+    let metas = share_hook_extra_metas(vault)?;
+    ExtraAccountMetaList::init::<ExecuteInstruction>(
+        &mut meta_list.try_borrow_mut_data()?,
+        &metas,
+    )?;
+    Ok(())
+}
+
+/// Reject hook calls made outside a Token-2022 transfer of the account
+fn check_transferring(share_account: &AccountInfo) -> Result<()> {
+    let data = share_account.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    let hook_account = state.get_extension::<TransferHookAccount>()?;
+    require!(
+        bool::from(hook_account.transferring),
+        VaultError::NotTransferring
+    );
+    Ok(())
+}
+
+// ============================================================================
+// Quote Verification
+// ============================================================================
+
+/// Size of the Ed25519 instruction header (num_signatures + padding)
+const ED25519_HEADER_LEN: usize = 2;
+/// Size of one Ed25519SignatureOffsets entry
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Verify that the instruction preceding this one is an Ed25519 program
+/// instruction checking `signer`'s signature over exactly `message`
+fn verify_quote_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = ix_sysvar::load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, VaultError::MissingQuoteSignature);
+    let ix =
+        ix_sysvar::load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+
+    require!(
+        ix.program_id == ed25519_program::ID,
+        VaultError::MissingQuoteSignature
+    );
+    require!(ix.accounts.is_empty(), VaultError::InvalidQuoteSignature);
+
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        VaultError::InvalidQuoteSignature
+    );
+
+    let read_u16 =
+        |offset: usize| -> usize { u16::from_le_bytes([data[offset], data[offset + 1]]) as usize };
+    let offsets = ED25519_HEADER_LEN;
+    let signature_ix_index = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4);
+    let pubkey_ix_index = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8);
+    let message_size = read_u16(offsets + 10);
+    let message_ix_index = read_u16(offsets + 12);
+
+    // Signature, key and message must all live in the Ed25519 instruction itself
+    let this_ix = u16::MAX as usize;
+    require!(
+        signature_ix_index == this_ix && pubkey_ix_index == this_ix && message_ix_index == this_ix,
+        VaultError::InvalidQuoteSignature
+    );
+
+    let pubkey = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(VaultError::InvalidQuoteSignature)?;
+    require!(pubkey == signer.as_ref(), VaultError::InvalidQuoteSignature);
+
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(VaultError::InvalidQuoteSignature)?;
+    require!(signed_message == message, VaultError::QuoteMismatch);
+
+    Ok(())
+}
+
+// ============================================================================
+// Oracle
+// ============================================================================
+
+/// Borsh layout of the Pyth receiver's PriceUpdateV2 account
+#[derive(AnchorDeserialize)]
+struct PriceUpdateV2 {
+    _write_authority: Pubkey,
+    verification_level: VerificationLevel,
+    price_message: PriceFeedMessage,
+    _posted_slot: u64,
+}
+
+#[derive(AnchorDeserialize, PartialEq)]
+enum VerificationLevel {
+    Partial { _num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorDeserialize)]
+struct PriceFeedMessage {
+    feed_id: [u8; 32],
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+    _prev_publish_time: i64,
+    _ema_price: i64,
+    _ema_conf: u64,
+}
+
+/// Oracle price that passed feed, staleness and confidence checks
+struct OraclePrice {
+    price: u64,
+    exponent: i32,
+    publish_time: i64,
+}
+
+impl OraclePrice {
+    /// Convert to premium_mint base units per whole underlying token
+    fn to_premium_units(&self, premium_decimals: u8) -> Result<u64> {
+        let scale = self
+            .exponent
+            .checked_add(premium_decimals as i32)
+            .ok_or(VaultError::Overflow)?;
+        let factor = 10u128
+            .checked_pow(scale.unsigned_abs())
+            .ok_or(VaultError::Overflow)?;
+        let value = if scale >= 0 {
+            (self.price as u128)
+                .checked_mul(factor)
+                .ok_or(VaultError::Overflow)?
+        } else {
+            (self.price as u128)
+                .checked_div(factor)
+                .ok_or(VaultError::Overflow)?
+        };
+        u64::try_from(value).map_err(|_| VaultError::Overflow.into())
+    }
+}
+
+/// Read and validate the vault's Pyth price from a PriceUpdateV2 account,
+/// published no earlier than not_before and at most max_price_staleness after
+fn load_oracle_price(
+    vault: &Vault,
+    price_update: &AccountInfo,
+    not_before: i64,
+) -> Result<OraclePrice> {
+    require!(
+        vault.price_feed_id != [0u8; 32],
+        VaultError::OracleNotConfigured
+    );
+    require!(
+        price_update.owner == &PYTH_RECEIVER_PROGRAM_ID,
+        VaultError::InvalidOracleAccount
+    );
+
+    let data = price_update.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR,
+        VaultError::InvalidOracleAccount
+    );
+    let update = PriceUpdateV2::deserialize(&mut &data[8..])
+        .map_err(|_| VaultError::InvalidOracleAccount)?;
+    let message = update.price_message;
+
+    require!(
+        update.verification_level == VerificationLevel::Full,
+        VaultError::InvalidOracleAccount
+    );
+    require!(
+        message.feed_id == vault.price_feed_id,
+        VaultError::OracleFeedMismatch
+    );
+
+    // Staleness check: a later price than the window cannot be picked instead
+    let latest_allowed = not_before
+        .checked_add(vault.max_price_staleness)
+        .ok_or(VaultError::Overflow)?;
+    require!(
+        message.publish_time >= not_before && message.publish_time <= latest_allowed,
+        VaultError::StaleOraclePrice
+    );
+
+    // Confidence check: conf / price must be within max_confidence_bps
+    require!(message.price > 0, VaultError::InvalidOraclePrice);
+    let price = message.price as u64;
+    let conf_bps = (message.conf as u128)
+        .checked_mul(10000)
+        .ok_or(VaultError::Overflow)?
+        .checked_div(price as u128)
+        .ok_or(VaultError::Overflow)?;
+    require!(
+        conf_bps <= vault.max_confidence_bps as u128,
+        VaultError::OracleConfidenceTooWide
+    );
+
+    Ok(OraclePrice {
+        price,
+        exponent: message.exponent,
+        publish_time: message.publish_time,
+    })
+}
+
+// ============================================================================
+// Account Structures
+// ============================================================================
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct Vault {
+    /// Admin: configuration, whitelist, fees, roles and closing the vault
+    pub authority: Pubkey,
+    /// Layout version (CURRENT_VAULT_VERSION), at the same offset in every
+    /// versioned layout so migrate_vault can read it before deserializing
+    pub version: u8,
+    #[max_len(64)]
+    pub asset_id: String,
+    pub underlying_mint: Pubkey,
+    pub share_mint: Pubkey,
+    pub vault_token_account: Pubkey,
+    // USDC premium escrow
+    pub premium_mint: Pubkey,
+    pub premium_token_account: Pubkey,
+    // State
+    pub total_assets: u64,
+    pub total_shares: u64,
+    /// Virtual offset for share calculations (prevents first-depositor attacks)
+    /// effective_shares = total_shares + virtual_offset
+    pub virtual_offset: u64,
+    pub epoch: u64,
+    pub utilization_cap_bps: u16,
+    pub min_epoch_duration: i64,
+    pub last_roll_timestamp: i64,
+    pub pending_withdrawals: u64,
+    // Notional-based exposure tracking (in collateral units)
+    pub epoch_notional_exposed: u64,
+    pub epoch_premium_earned: u64,
+    pub epoch_premium_per_token_bps: u32,
+    /// Accumulated USDC premium balance (separate from underlying TVL)
+    pub premium_balance_usdc: u64,
+    /// SECURITY: Emergency pause flag
+    pub is_paused: bool,
+    /// Pyth price feed used by settle_epoch
+    pub price_feed_id: [u8; 32],
+    pub max_price_staleness: i64,
+    pub max_confidence_bps: u16,
+    /// Option positions not yet settled and closed
+    pub open_positions: u32,
+    /// How ITM positions are settled
+    pub settlement_mode: SettlementMode,
+    /// Covered calls on the underlying or cash-secured puts
+    pub strategy: Strategy,
+    pub underlying_decimals: u8,
+    /// Put vaults: underlying received on ITM assignment, distributed on withdrawal
+    pub assigned_underlying: u64,
+    /// Fees
+    pub fee_recipient: Pubkey,
+    pub management_fee_bps: u16,
+    pub performance_fee_bps: u16,
+    pub deposit_fee_bps: u16,
+    pub withdrawal_fee_bps: u16,
+    /// Highest post-fee value_per_share at which a performance fee was charged
+    pub high_water_mark: u64,
+    /// Deposits are queued and admitted at the next epoch roll
+    pub deposit_queue_enabled: bool,
+    /// Queued collateral not yet in total_assets
+    pub pending_deposits: u64,
+    /// Shares requested for withdrawal this epoch (exit at the next roll)
+    pub queued_withdrawal_shares: u64,
+    /// Value of exited but unprocessed withdrawals, excluded from
+    /// total_assets, premium_balance_usdc and assigned_underlying
+    pub withdrawal_reserve: u64,
+    pub premium_withdrawal_reserve: u64,
+    pub underlying_withdrawal_reserve: u64,
+    /// Cumulative premium credited per share (PRICE_PER_SHARE_SCALE)
+    pub premium_per_share_index: u128,
+    /// Sum of shares x premium_per_share_index at request over this epoch's
+    /// withdrawal requests; they exit with the premium credited since
+    pub queued_withdrawal_index_weight: u128,
+    /// Premium compounding
+    pub swap_program: Pubkey,
+    pub compound_bps: u16,
+    /// Premium set aside at the roll, awaiting compound_premium
+    pub premium_to_compound: u64,
+    /// Token programs owning the underlying and premium mints
+    pub underlying_token_program: Pubkey,
+    pub premium_token_program: Pubkey,
+    /// UI tokens per raw underlying token (PRICE_PER_SHARE_SCALE), changed by
+    /// corporate actions
+    pub underlying_multiplier: u64,
+    /// Multiplier of a queued AdminAction::CorporateAction (0 when none) and
+    /// when it becomes executable
+    pub pending_underlying_multiplier: u64,
+    pub corporate_action_unlock_time: i64,
+    /// Runs epochs, records fills and collects premium
+    pub keeper: Pubkey,
+    /// Can pause the vault, nothing else
+    pub guardian: Pubkey,
+    /// Settles epochs and pays ITM settlements
+    pub settlement_operator: Pubkey,
+    /// Two-step admin rotation (see AdminAction::TransferAuthority)
+    pub pending_authority: Pubkey,
+    pub authority_transfer_unlock_time: i64,
+    /// Delay applied to every timelocked admin change
+    pub timelock_delay: i64,
+    /// Id of the next PendingAction
+    pub next_action_id: u64,
+    /// Deposit caps in collateral units (0 = uncapped)
+    pub max_total_assets: u64,
+    pub max_deposit_per_user: u64,
+    /// Issues depositor attestations; Pubkey::default() disables the gate
+    pub attester: Pubkey,
+    pub bump: u8,
+    /// Latest settlement price (premium_mint per whole raw underlying token),
+    /// used to value the vault for the high-water mark
+    pub last_settlement_price: u64,
+    /// Premium that reached premium_token_account this epoch; credited at the
+    /// roll (epoch_premium_earned also counts fills whose premium is unpaid)
+    pub epoch_premium_received: u64,
+    /// Hookless share mint of a migrated version 0 vault, converted 1:1 to
+    /// share_mint by convert_legacy_shares (default when never migrated)
+    pub legacy_share_mint: Pubkey,
+    /// Padding for future fields, so later layouts can grow without a realloc
+    pub reserved: [u64; 16],
+}
+
+impl Vault {
+    /// Mint depositors provide as collateral
+    pub fn collateral_mint(&self) -> Pubkey {
+        match self.strategy {
+            Strategy::CoveredCall => self.underlying_mint,
+            Strategy::CashSecuredPut => self.premium_mint,
+        }
+    }
+
+    /// Token account holding the collateral counted in total_assets
+    pub fn collateral_token_account(&self) -> Pubkey {
+        match self.strategy {
+            Strategy::CoveredCall => self.vault_token_account,
+            Strategy::CashSecuredPut => self.premium_token_account,
+        }
+    }
+
+    /// Token program of the collateral mint
+    pub fn collateral_token_program(&self) -> Pubkey {
+        match self.strategy {
+            Strategy::CoveredCall => self.underlying_token_program,
+            Strategy::CashSecuredPut => self.premium_token_program,
+        }
+    }
+
+    /// Convert a price per whole UI underlying token to a price per whole raw token
+    pub fn to_raw_price(&self, ui_price: u64) -> Result<u64> {
+        let raw_price = (ui_price as u128)
+            .checked_mul(self.underlying_multiplier as u128)
+            .ok_or(VaultError::Overflow)?
+            .checked_div(PRICE_PER_SHARE_SCALE)
+            .ok_or(VaultError::Overflow)?;
+        u64::try_from(raw_price).map_err(|_| VaultError::Overflow.into())
+    }
+
+    /// Underlying owed to shareholders and withdrawers, held in vault_token_account
+    pub fn tracked_underlying_balance(&self) -> Result<u64> {
+        let tracked = match self.strategy {
+            Strategy::CoveredCall => self
+                .total_assets
+                .checked_add(self.pending_deposits)
+                .and_then(|v| v.checked_add(self.withdrawal_reserve)),
+            Strategy::CashSecuredPut => self
+                .assigned_underlying
+                .checked_add(self.underlying_withdrawal_reserve),
+        };
+        tracked.ok_or(VaultError::Overflow.into())
+    }
+
+    /// Collateral, premium and reserves held in premium_token_account,
+    /// including premium received this epoch and credited at the next roll
+    pub fn tracked_premium_balance(&self) -> Result<u64> {
+        self.collateral_in_premium_account()
+            .checked_add(self.premium_balance_usdc)
+            .and_then(|v| v.checked_add(self.premium_withdrawal_reserve))
+            .and_then(|v| v.checked_add(self.premium_to_compound))
+            .and_then(|v| v.checked_add(self.epoch_premium_received))
+            .ok_or(VaultError::Overflow.into())
+    }
+
+    /// Gated vaults (attester set) admit capital and share transfers only for
+    /// wallets with an unexpired attestation issued by the current attester;
+    /// rotating the attester invalidates old attestations. Exits are never gated
+    pub fn check_attestation(&self, attestation: Option<&Attestation>, now: i64) -> Result<()> {
+        if self.attester == Pubkey::default() {
+            return Ok(());
+        }
+        let attestation = attestation.ok_or(VaultError::AttestationRequired)?;
+        require!(
+            attestation.attester == self.attester,
+            VaultError::AttestationRequired
+        );
+        require!(attestation.expires_at > now, VaultError::AttestationExpired);
+        Ok(())
+    }
+
+    /// Enforce max_total_assets and max_deposit_per_user for a deposit of
+    /// amount by a user who has user_deposited outstanding
+    pub fn check_deposit_caps(&self, user_deposited: u64, amount: u64) -> Result<()> {
+        if self.max_total_assets > 0 {
+            let tvl = self
+                .total_assets
+                .checked_add(self.pending_deposits)
+                .and_then(|v| v.checked_add(amount))
+                .ok_or(VaultError::Overflow)?;
+            require!(tvl <= self.max_total_assets, VaultError::ExceedsTvlCap);
+        }
+        if self.max_deposit_per_user > 0 {
+            let deposited = user_deposited
+                .checked_add(amount)
+                .ok_or(VaultError::Overflow)?;
+            require!(
+                deposited <= self.max_deposit_per_user,
+                VaultError::ExceedsUserDepositCap
+            );
+        }
+        Ok(())
+    }
+
+    /// Collateral (including queued deposits) held in the premium account
+    pub fn collateral_in_premium_account(&self) -> u64 {
+        match self.strategy {
+            Strategy::CoveredCall => 0,
+            Strategy::CashSecuredPut => self
+                .total_assets
+                .saturating_add(self.pending_deposits)
+                .saturating_add(self.withdrawal_reserve),
+        }
+    }
+
+    /// Collateral per share, scaled by PRICE_PER_SHARE_SCALE
+    /// Uses effective shares (total_shares + virtual_offset)
+    pub fn price_per_share(&self) -> Result<u64> {
+        if self.total_shares == 0 && self.virtual_offset == 0 {
+            return Ok(PRICE_PER_SHARE_SCALE as u64);
+        }
+        self.per_share(self.total_assets)
+    }
+
+    /// Total value per share in premium_mint units (PRICE_PER_SHARE_SCALE):
+    /// collateral, assigned underlying and premium awaiting compounding valued
+    /// at price (premium_mint per whole raw underlying token), plus the
+    /// cumulative premium index, so premium paid out counts toward recovery
+    pub fn value_per_share(&self, price: u64) -> Result<u64> {
+        let underlying_value = |amount: u64| -> Result<u128> {
+            let underlying_unit = 10u128
+                .checked_pow(self.underlying_decimals as u32)
+                .ok_or(VaultError::Overflow)?;
+            (amount as u128)
+                .checked_mul(price as u128)
+                .ok_or(VaultError::Overflow)?
+                .checked_div(underlying_unit)
+                .ok_or(VaultError::Overflow.into())
+        };
+        let holdings =
+            match self.strategy {
+                Strategy::CoveredCall => underlying_value(self.total_assets)?
+                    .checked_add(self.premium_to_compound as u128),
+                Strategy::CashSecuredPut => underlying_value(self.assigned_underlying)?
+                    .checked_add(self.total_assets as u128),
+            }
+            .ok_or(VaultError::Overflow)?;
+        let holdings = u64::try_from(holdings).map_err(|_| VaultError::Overflow)?;
+        let value = (self.per_share(holdings)? as u128)
+            .checked_add(self.premium_per_share_index)
+            .ok_or(VaultError::Overflow)?;
+        u64::try_from(value).map_err(|_| VaultError::Overflow.into())
+    }
+
+    /// Record premium paid into premium_token_account, credited at the next roll
+    pub fn record_premium_received(&mut self, amount: u64) -> Result<()> {
+        self.epoch_premium_received = self
+            .epoch_premium_received
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+
+    /// Credited premium that premium_token_account can actually pay out
+    pub fn available_premium(&self, account_balance: u64) -> u64 {
+        self.premium_balance_in(account_balance)
+            .min(self.premium_balance_usdc)
+    }
+
+    /// premium_token_account's balance less collateral, reserves and premium
+    /// not yet credited: what premium_balance_usdc should be
+    pub fn premium_balance_in(&self, account_balance: u64) -> u64 {
+        account_balance
+            .saturating_sub(self.collateral_in_premium_account())
+            .saturating_sub(self.premium_withdrawal_reserve)
+            .saturating_sub(self.premium_to_compound)
+            .saturating_sub(self.epoch_premium_received)
+    }
+
+    /// Book an epoch's net premium, returning the part compounded
+    /// Compounding vaults fold compound_bps back into total_assets: covered
+    /// calls via compound_premium's swap, puts directly (premium is collateral).
+    /// The rest is credited to premium_balance_usdc (separate from TVL, so no
+    /// synthetic flywheel) and accrues to holders through the index
+    pub fn book_epoch_premium(&mut self, net_premium: u64) -> Result<u64> {
+        let premium_compounded = bps_of(net_premium, self.compound_bps)?;
+        match self.strategy {
+            Strategy::CoveredCall => {
+                self.premium_to_compound = self
+                    .premium_to_compound
+                    .checked_add(premium_compounded)
+                    .ok_or(VaultError::Overflow)?;
+            }
+            Strategy::CashSecuredPut => {
+                self.total_assets = self
+                    .total_assets
+                    .checked_add(premium_compounded)
+                    .ok_or(VaultError::Overflow)?;
+            }
+        }
+        self.credit_premium(net_premium - premium_compounded)?;
+        Ok(premium_compounded)
+    }
+
+    /// Move swapped premium out of premium_to_compound and the underlying
+    /// bought with it into total_assets
+    pub fn record_compounded(
+        &mut self,
+        premium_spent: u64,
+        underlying_received: u64,
+    ) -> Result<()> {
+        self.premium_to_compound = self
+            .premium_to_compound
+            .checked_sub(premium_spent)
+            .ok_or(VaultError::Overflow)?;
+        self.total_assets = self
+            .total_assets
+            .checked_add(underlying_received)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+
+    /// Credit USDC to premium_balance_usdc and the premium-per-share index
+    pub fn credit_premium(&mut self, amount: u64) -> Result<()> {
+        let per_share = self.per_share(amount)?;
+        self.premium_per_share_index = self
+            .premium_per_share_index
+            .checked_add(per_share as u128)
+            .ok_or(VaultError::Overflow)?;
+        self.premium_balance_usdc = self
+            .premium_balance_usdc
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+
+    /// Assigned underlying per share, scaled by PRICE_PER_SHARE_SCALE
+    pub fn assigned_underlying_per_share(&self) -> Result<u64> {
+        self.per_share(self.assigned_underlying)
+    }
+
+    fn per_share(&self, amount: u64) -> Result<u64> {
+        let effective_shares = self
+            .total_shares
+            .checked_add(self.virtual_offset)
+            .ok_or(VaultError::Overflow)?;
+        if effective_shares == 0 {
+            return Ok(0);
+        }
+        let value = (amount as u128)
+            .checked_mul(PRICE_PER_SHARE_SCALE)
+            .ok_or(VaultError::Overflow)?
+            .checked_div(effective_shares as u128)
+            .ok_or(VaultError::Overflow)?;
+        u64::try_from(value).map_err(|_| VaultError::Overflow.into())
+    }
+
+    /// Queue burned shares to exit at the next roll; returns the premium index
+    /// they were checkpointed at
+    pub fn queue_withdrawal(&mut self, shares: u64) -> Result<u128> {
+        self.pending_withdrawals = self
+            .pending_withdrawals
+            .checked_add(shares)
+            .ok_or(VaultError::Overflow)?;
+        self.queued_withdrawal_shares = self
+            .queued_withdrawal_shares
+            .checked_add(shares)
+            .ok_or(VaultError::Overflow)?;
+        self.queued_withdrawal_index_weight = (shares as u128)
+            .checked_mul(self.premium_per_share_index)
+            .and_then(|weight| weight.checked_add(self.queued_withdrawal_index_weight))
+            .ok_or(VaultError::Overflow)?;
+        Ok(self.premium_per_share_index)
+    }
+
+    /// Take back shares queued by queue_withdrawal before they exit
+    pub fn unqueue_withdrawal(
+        &mut self,
+        shares: u64,
+        premium_index_at_request: u128,
+    ) -> Result<()> {
+        self.pending_withdrawals = self
+            .pending_withdrawals
+            .checked_sub(shares)
+            .ok_or(VaultError::Overflow)?;
+        self.queued_withdrawal_shares = self
+            .queued_withdrawal_shares
+            .checked_sub(shares)
+            .ok_or(VaultError::Overflow)?;
+        self.queued_withdrawal_index_weight = (shares as u128)
+            .checked_mul(premium_index_at_request)
+            .and_then(|weight| self.queued_withdrawal_index_weight.checked_sub(weight))
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+
+    /// Exit the shares queued this epoch at the current per-share values,
+    /// recording them in snapshot. Their collateral, premium credited since
+    /// their requests and assigned underlying move into reserves so later
+    /// epochs' P&L no longer applies; returns the collateral reserved
+    pub fn exit_queued_withdrawals(&mut self, snapshot: &mut EpochSnapshot) -> Result<u64> {
+        let withdrawal_shares = self.queued_withdrawal_shares;
+        snapshot.price_per_share = self.price_per_share()?;
+        snapshot.premium_index = self.premium_per_share_index;
+        snapshot.assigned_underlying_per_share = self.assigned_underlying_per_share()?;
+        snapshot.withdrawal_shares = withdrawal_shares;
+
+        let withdrawal_amount = snapshot.collateral_for(withdrawal_shares)?;
+        let withdrawal_premium = u64::try_from(
+            (withdrawal_shares as u128)
+                .checked_mul(self.premium_per_share_index)
+                .and_then(|weight| weight.checked_sub(self.queued_withdrawal_index_weight))
+                .ok_or(VaultError::Overflow)?
+                / PRICE_PER_SHARE_SCALE,
+        )
+        .map_err(|_| VaultError::Overflow)?;
+        let withdrawal_underlying = snapshot.assigned_underlying_for(withdrawal_shares)?;
+
+        self.total_assets = self
+            .total_assets
+            .checked_sub(withdrawal_amount)
+            .ok_or(VaultError::Overflow)?;
+        self.withdrawal_reserve = self
+            .withdrawal_reserve
+            .checked_add(withdrawal_amount)
+            .ok_or(VaultError::Overflow)?;
+        self.premium_balance_usdc = self
+            .premium_balance_usdc
+            .checked_sub(withdrawal_premium)
+            .ok_or(VaultError::Overflow)?;
+        self.premium_withdrawal_reserve = self
+            .premium_withdrawal_reserve
+            .checked_add(withdrawal_premium)
+            .ok_or(VaultError::Overflow)?;
+        self.assigned_underlying = self
+            .assigned_underlying
+            .checked_sub(withdrawal_underlying)
+            .ok_or(VaultError::Overflow)?;
+        self.underlying_withdrawal_reserve = self
+            .underlying_withdrawal_reserve
+            .checked_add(withdrawal_underlying)
+            .ok_or(VaultError::Overflow)?;
+        self.total_shares = self
+            .total_shares
+            .checked_sub(withdrawal_shares)
+            .ok_or(VaultError::Overflow)?;
+        self.queued_withdrawal_shares = 0;
+        self.queued_withdrawal_index_weight = 0;
+        Ok(withdrawal_amount)
+    }
+
+    /// Pay out an exited withdrawal from the reserves at the values fixed by
+    /// the snapshot of its exit roll. The withdrawal fee is taken in shares,
+    /// which re-enter the vault together with their reserved value
+    pub fn redeem_exited_withdrawal(
+        &mut self,
+        snapshot: &EpochSnapshot,
+        shares: u64,
+        premium_index_at_request: u128,
+    ) -> Result<WithdrawalPayout> {
+        let fee_shares = bps_of(shares, self.withdrawal_fee_bps)?;
+        let redeemed_shares = shares.checked_sub(fee_shares).ok_or(VaultError::Overflow)?;
+
+        let payout = WithdrawalPayout {
+            collateral: snapshot.collateral_for(redeemed_shares)?,
+            premium: snapshot.premium_for(redeemed_shares, premium_index_at_request)?,
+            underlying: snapshot.assigned_underlying_for(redeemed_shares)?,
+            fee_shares,
+        };
+        let fee_amount = snapshot.collateral_for(fee_shares)?;
+        let fee_premium = snapshot.premium_for(fee_shares, premium_index_at_request)?;
+        let fee_underlying = snapshot.assigned_underlying_for(fee_shares)?;
+
+        // SECURITY FIX H-1: Verify vault has sufficient reserved assets
+        require!(
+            self.withdrawal_reserve >= payout.collateral,
+            VaultError::InsufficientVaultBalance
+        );
+
+        self.withdrawal_reserve = self
+            .withdrawal_reserve
+            .checked_sub(payout.collateral)
+            .and_then(|r| r.checked_sub(fee_amount))
+            .ok_or(VaultError::Overflow)?;
+        self.premium_withdrawal_reserve = self
+            .premium_withdrawal_reserve
+            .checked_sub(payout.premium)
+            .and_then(|r| r.checked_sub(fee_premium))
+            .ok_or(VaultError::Overflow)?;
+        self.underlying_withdrawal_reserve = self
+            .underlying_withdrawal_reserve
+            .checked_sub(payout.underlying)
+            .and_then(|r| r.checked_sub(fee_underlying))
+            .ok_or(VaultError::Overflow)?;
+        self.total_assets = self
+            .total_assets
+            .checked_add(fee_amount)
+            .ok_or(VaultError::Overflow)?;
+        self.premium_balance_usdc = self
+            .premium_balance_usdc
+            .checked_add(fee_premium)
+            .ok_or(VaultError::Overflow)?;
+        self.assigned_underlying = self
+            .assigned_underlying
+            .checked_add(fee_underlying)
+            .ok_or(VaultError::Overflow)?;
+        self.total_shares = self
+            .total_shares
+            .checked_add(fee_shares)
+            .ok_or(VaultError::Overflow)?;
+        self.pending_withdrawals = self
+            .pending_withdrawals
+            .checked_sub(shares)
+            .ok_or(VaultError::Overflow)?;
+        Ok(payout)
+    }
+
+    /// Start the timelock for action, returning it as the next PendingAction
+    pub fn queue_action(
+        &mut self,
+        vault: Pubkey,
+        action: AdminAction,
+        now: i64,
+        bump: u8,
+    ) -> Result<PendingAction> {
+        action.validate()?;
+
+        let executable_at = now
+            .checked_add(self.timelock_delay)
+            .ok_or(VaultError::Overflow)?;
+        // One corporate action at a time; settle_epoch waits for it
+        if let AdminAction::CorporateAction { new_multiplier } = action {
+            require!(
+                self.pending_underlying_multiplier == 0,
+                VaultError::CorporateActionPending
+            );
+            self.pending_underlying_multiplier = new_multiplier;
+            self.corporate_action_unlock_time = executable_at;
+        }
+
+        let id = self.next_action_id;
+        self.next_action_id = id.checked_add(1).ok_or(VaultError::Overflow)?;
+        Ok(PendingAction {
+            vault,
+            id,
+            action,
+            queued_at: now,
+            executable_at,
+            bump,
+        })
+    }
+}
+
+/// What redeem_exited_withdrawal pays the user, and the fee shares minted
+/// to the fee recipient
+pub struct WithdrawalPayout {
+    pub collateral: u64,
+    pub premium: u64,
+    pub underlying: u64,
+    pub fee_shares: u64,
+}
+
+/// Off-chain check passed by a wallet, issued by the vault's attester
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub vault: Pubkey,
+    pub wallet: Pubkey,
+    pub attester: Pubkey,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+/// Collateral a wallet has deposited net of withdrawals, for max_deposit_per_user
+/// Tracks the depositing wallet, not share transfers
+#[account]
+#[derive(InitSpace)]
+pub struct UserDeposits {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub deposited: u64,
+    pub bump: u8,
+}
+
+impl UserDeposits {
+    pub fn record_deposit(
+        &mut self,
+        vault: Pubkey,
+        user: Pubkey,
+        bump: u8,
+        amount: u64,
+    ) -> Result<()> {
+        self.vault = vault;
+        self.user = user;
+        self.bump = bump;
+        self.deposited = self
+            .deposited
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Premium accrual state for a single share token account
+#[account]
+#[derive(InitSpace)]
+pub struct PremiumCheckpoint {
+    pub vault: Pubkey,
+    pub share_account: Pubkey,
+    /// Vault premium_per_share_index at the last checkpoint
+    pub premium_index: u128,
+    /// Share balance at the last checkpoint
+    pub shares: u64,
+    /// Premium accrued but not yet claimed
+    pub accrued: u64,
+    pub bump: u8,
+}
+
+impl PremiumCheckpoint {
+    /// Accrue premium credited since the last checkpoint and record the new
+    /// balance. Every share movement syncs the checkpoint, so shares equals
+    /// the balance; the min only guards burns made outside the vault
+    pub fn accrue(&mut self, premium_index: u128, balance: u64, new_balance: u64) -> Result<()> {
+        let index_delta = premium_index
+            .checked_sub(self.premium_index)
+            .ok_or(VaultError::Overflow)?;
+        let earned = (self.shares.min(balance) as u128)
+            .checked_mul(index_delta)
+            .ok_or(VaultError::Overflow)?
+            .checked_div(PRICE_PER_SHARE_SCALE)
+            .ok_or(VaultError::Overflow)?;
+
+        self.accrued = self
+            .accrued
+            .checked_add(u64::try_from(earned).map_err(|_| VaultError::Overflow)?)
+            .ok_or(VaultError::Overflow)?;
+        self.premium_index = premium_index;
+        self.shares = new_balance;
+        Ok(())
+    }
+
+    /// Accrue premium credited since since_index to shares the checkpoint
+    /// did not track: burned by a cancelled withdrawal, or converted from the
+    /// legacy share mint (since index 0, the migration)
+    pub fn accrue_since(
+        &mut self,
+        premium_index: u128,
+        since_index: u128,
+        shares: u64,
+    ) -> Result<()> {
+        let earned = index_value(
+            shares,
+            premium_index
+                .checked_sub(since_index)
+                .ok_or(VaultError::Overflow)?,
+        )?;
+        self.accrued = self
+            .accrued
+            .checked_add(earned)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Collateral queued for admission at the next epoch roll
+#[account]
+#[derive(InitSpace)]
+pub struct DepositRequest {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub request_epoch: u64,
+    pub bump: u8,
+}
+
+/// History of a closed epoch, written by the advance_epoch that closed it:
+/// a summary so clients can read vault history without replaying events, and
+/// the per-share values its withdrawals exited and deposits were admitted at
+#[account]
+#[derive(InitSpace, Default)]
+pub struct EpochSnapshot {
+    pub vault: Pubkey,
+    /// The closed epoch (seed)
+    pub epoch: u64,
+    /// Per-share values (PRICE_PER_SHARE_SCALE) withdrawals exited and
+    /// queued deposits were admitted at
+    pub price_per_share: u64,
+    /// Premium-per-share index at this roll; exited withdrawals are paid
+    /// the premium credited since their request
+    pub premium_index: u128,
+    pub assigned_underlying_per_share: u64,
+    pub withdrawal_shares: u64,
+    pub deposits_admitted: u64,
+    pub deposit_shares: u64,
+    /// Start and end of the closed epoch
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub notional_exposed: u64,
+    /// Gross premium received and credited at the roll (before performance fee)
+    pub premium_earned: u64,
+    pub avg_premium_bps: u32,
+    /// Collateral paid or delivered to ITM positions
+    pub settlement_paid: u64,
+    /// Oracle settlement price (0 when no options were sold)
+    pub settlement_price: u64,
+    pub bump: u8,
+}
+
+impl EpochSnapshot {
+    pub fn collateral_for(&self, shares: u64) -> Result<u64> {
+        per_share_value(shares, self.price_per_share)
+    }
+
+    pub fn premium_for(&self, shares: u64, premium_index_at_request: u128) -> Result<u64> {
+        index_value(
+            shares,
+            self.premium_index
+                .checked_sub(premium_index_at_request)
+                .ok_or(VaultError::Overflow)?,
+        )
+    }
+
+    pub fn assigned_underlying_for(&self, shares: u64) -> Result<u64> {
+        per_share_value(shares, self.assigned_underlying_per_share)
+    }
+}
+
+/// shares * index_delta / PRICE_PER_SHARE_SCALE for premium index deltas,
+/// rounded down
+fn index_value(shares: u64, index_delta: u128) -> Result<u64> {
+    let value = (shares as u128)
+        .checked_mul(index_delta)
+        .ok_or(VaultError::Overflow)?
+        .checked_div(PRICE_PER_SHARE_SCALE)
+        .ok_or(VaultError::Overflow)?;
+    u64::try_from(value).map_err(|_| VaultError::Overflow.into())
+}
+
+/// shares * per_share / PRICE_PER_SHARE_SCALE, rounded down
+fn per_share_value(shares: u64, per_share: u64) -> Result<u64> {
+    let value = (shares as u128)
+        .checked_mul(per_share as u128)
+        .ok_or(VaultError::Overflow)?
+        .checked_div(PRICE_PER_SHARE_SCALE)
+        .ok_or(VaultError::Overflow)?;
+    u64::try_from(value).map_err(|_| VaultError::Overflow.into())
+}
+
+/// Layout version of a stored Vault account: version 0 accounts predate the
+/// field and all have the same size, later layouts store it at a fixed offset
+fn stored_vault_version(data: &[u8]) -> Result<u8> {
+    require!(
+        data.len() > VAULT_VERSION_OFFSET && &data[..8] == Vault::DISCRIMINATOR,
+        VaultError::InvalidVaultLayout
+    );
+    if data.len() == VAULT_V0_LEN {
+        return Ok(0);
+    }
+    Ok(data[VAULT_VERSION_OFFSET])
+}
+
+/// Vault layout before versioning (version 0), read by migrate_vault
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyVaultV0 {
+    pub authority: Pubkey,
+    pub asset_id: String,
+    pub underlying_mint: Pubkey,
+    pub share_mint: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub premium_mint: Pubkey,
     pub premium_token_account: Pubkey,
-    // Share escrow for withdrawals
     pub share_escrow: Pubkey,
-    // State
     pub total_assets: u64,
     pub total_shares: u64,
-    /// Virtual offset for share calculations (prevents first-depositor attacks)
-    /// effective_shares = total_shares + virtual_offset
     pub virtual_offset: u64,
     pub epoch: u64,
     pub utilization_cap_bps: u16,
     pub min_epoch_duration: i64,
     pub last_roll_timestamp: i64,
     pub pending_withdrawals: u64,
-    // Notional-based exposure tracking
     pub epoch_notional_exposed: u64,
     pub epoch_premium_earned: u64,
     pub epoch_premium_per_token_bps: u32,
-    /// Accumulated USDC premium balance (separate from underlying TVL)
     pub premium_balance_usdc: u64,
-    /// SECURITY: Emergency pause flag
     pub is_paused: bool,
-    /// SECURITY FIX M-3: Timelock for parameter changes
     pub pending_min_epoch_duration: i64,
     pub pending_utilization_cap: u16,
     pub param_change_unlock_time: i64,
     pub bump: u8,
 }
 
-#[account]
-pub struct WithdrawalRequest {
-    pub user: Pubkey,
-    pub vault: Pubkey,
-    pub shares: u64,
-    pub request_epoch: u64,
-    pub processed: bool,
+impl LegacyVaultV0 {
+    /// Current layout of a legacy vault: new fields take initialize_vault
+    /// defaults, roles start as the authority. The legacy premium balance is
+    /// credited from index 0, where converted legacy shares start accruing
+    pub fn into_vault(
+        self,
+        share_mint: Pubkey,
+        underlying_decimals: u8,
+        underlying_multiplier: u64,
+        underlying_token_program: Pubkey,
+        premium_token_program: Pubkey,
+    ) -> Result<Vault> {
+        let authority = self.authority;
+        let mut vault = Vault {
+            authority,
+            asset_id: self.asset_id,
+            underlying_mint: self.underlying_mint,
+            share_mint,
+            vault_token_account: self.vault_token_account,
+            premium_mint: self.premium_mint,
+            premium_token_account: self.premium_token_account,
+            total_assets: self.total_assets,
+            total_shares: self.total_shares,
+            virtual_offset: self.virtual_offset,
+            epoch: self.epoch,
+            utilization_cap_bps: self.utilization_cap_bps,
+            min_epoch_duration: self.min_epoch_duration,
+            last_roll_timestamp: self.last_roll_timestamp,
+            epoch_notional_exposed: self.epoch_notional_exposed,
+            epoch_premium_earned: self.epoch_premium_earned,
+            epoch_premium_per_token_bps: self.epoch_premium_per_token_bps,
+            is_paused: self.is_paused,
+            underlying_decimals,
+            underlying_token_program,
+            premium_token_program,
+            underlying_multiplier,
+            fee_recipient: authority,
+            keeper: authority,
+            guardian: authority,
+            settlement_operator: authority,
+            timelock_delay: DEFAULT_TIMELOCK_DELAY,
+            version: CURRENT_VAULT_VERSION,
+            legacy_share_mint: self.share_mint,
+            bump: self.bump,
+            ..Vault::default()
+        };
+        vault.credit_premium(self.premium_balance_usdc)?;
+        Ok(vault)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawalRequest {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub shares: u64,
+    pub request_epoch: u64,
+    pub processed: bool,
+    /// Vault premium_per_share_index when the shares were burned; premium
+    /// before it was checkpointed, premium after it is paid on processing
+    pub premium_index_at_request: u128,
+}
+
+/// Oracle settlement for a single epoch
+#[account]
+#[derive(InitSpace)]
+pub struct EpochSettlement {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    /// Number of positions opened this epoch (next position index)
+    pub position_count: u32,
+    /// Sum of premium over all positions
+    pub total_premium: u64,
+    /// Oracle price in premium_mint base units per whole raw underlying token
+    pub settlement_price: u64,
+    pub publish_time: i64,
+    pub underlying_decimals: u8,
+    /// When settle_epoch ran (opens the physical exercise window)
+    pub settled_at: i64,
+    /// Collateral paid or delivered to ITM positions
+    pub total_paid: u64,
+    pub is_settled: bool,
+    pub bump: u8,
+}
+
+/// A single option sold by the vault on an RFQ fill
+/// Created by record_notional_exposure, closed by pay_settlement
+#[account]
+#[derive(InitSpace)]
+pub struct OptionPosition {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    pub index: u32,
+    /// Market maker that bought the option
+    pub counterparty: Pubkey,
+    pub option_type: OptionType,
+    /// Strike in premium_mint base units per whole raw underlying token
+    /// (the quoted strike scaled by underlying_multiplier at fill time)
+    pub strike_price: u64,
+    pub expiry: i64,
+    /// Notional in underlying tokens
+    pub notional: u64,
+    /// Premium in premium_mint tokens
+    pub premium: u64,
+    pub bump: u8,
+}
+
+impl OptionPosition {
+    /// ITM value in premium_mint tokens at the epoch's settlement price
+    pub fn intrinsic_value(&self, settlement: &EpochSettlement) -> Result<u64> {
+        let price = settlement.settlement_price;
+        let intrinsic = match self.option_type {
+            OptionType::Call if price > self.strike_price => price - self.strike_price,
+            OptionType::Put if price < self.strike_price => self.strike_price - price,
+            _ => return Ok(0),
+        };
+        let underlying_unit = 10u128
+            .checked_pow(settlement.underlying_decimals as u32)
+            .ok_or(VaultError::Overflow)?;
+        let value = (self.notional as u128)
+            .checked_mul(intrinsic as u128)
+            .ok_or(VaultError::Overflow)?
+            .checked_div(underlying_unit)
+            .ok_or(VaultError::Overflow)?;
+        u64::try_from(value).map_err(|_| VaultError::Overflow.into())
+    }
+
+    /// Strike * notional in premium_mint tokens (physical delivery cost)
+    pub fn strike_value(&self, settlement: &EpochSettlement) -> Result<u64> {
+        notional_value(
+            self.notional,
+            self.strike_price,
+            settlement.underlying_decimals,
+        )
+    }
+
+    /// Cash settlement amount in collateral units
+    /// Calls convert the intrinsic value to underlying at the settlement price;
+    /// puts pay the intrinsic value in premium_mint
+    pub fn collateral_payout(&self, settlement: &EpochSettlement) -> Result<u64> {
+        match self.option_type {
+            OptionType::Call => {
+                if settlement.settlement_price <= self.strike_price {
+                    return Ok(0);
+                }
+                let payout = (self.notional as u128)
+                    .checked_mul((settlement.settlement_price - self.strike_price) as u128)
+                    .ok_or(VaultError::Overflow)?
+                    .checked_div(settlement.settlement_price as u128)
+                    .ok_or(VaultError::Overflow)?;
+                u64::try_from(payout).map_err(|_| VaultError::Overflow.into())
+            }
+            OptionType::Put => self.intrinsic_value(settlement),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Default)]
+pub enum Strategy {
+    /// Holds the underlying and sells calls
+    #[default]
+    CoveredCall,
+    /// Holds premium_mint collateral and sells puts
+    CashSecuredPut,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Default)]
+pub enum SettlementMode {
+    /// ITM value is paid out of the vault's collateral
+    #[default]
+    Cash,
+    /// Underlying is exchanged for the strike
+    Physical,
+}
+
+/// Admin changes that must wait out the governance timelock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum AdminAction {
+    /// Adds or re-activates a maker, or updates its notional limit
+    AddMarketMaker {
+        market_maker: Pubkey,
+        notional_limit: u64,
+    },
+    Unpause,
+    /// Deposit caps of 0 remove the cap
+    ParamChange {
+        min_epoch_duration: Option<i64>,
+        utilization_cap_bps: Option<u16>,
+        max_total_assets: Option<u64>,
+        max_deposit_per_user: Option<u64>,
+    },
+    SetRoles {
+        keeper: Pubkey,
+        guardian: Pubkey,
+        settlement_operator: Pubkey,
+    },
+    SetFeeConfig {
+        fee_recipient: Pubkey,
+        management_fee_bps: u16,
+        performance_fee_bps: u16,
+        deposit_fee_bps: u16,
+        withdrawal_fee_bps: u16,
+    },
+    SetTimelockDelay {
+        delay: i64,
+    },
+    /// Pubkey::default() removes the depositor gate
+    SetAttester {
+        attester: Pubkey,
+    },
+    /// compound_bps of each epoch's net premium is added to total_assets
+    /// instead of being paid out; covered-call vaults swap it into underlying
+    /// through swap_program (see compound_premium)
+    SetCompoundConfig {
+        swap_program: Pubkey,
+        compound_bps: u16,
+    },
+    /// Pyth price feed used by settle_epoch; executes only without exposure
+    SetOracleConfig {
+        price_feed_id: [u8; 32],
+        max_price_staleness: i64,
+        max_confidence_bps: u16,
+    },
+    /// Executes only while no option positions are open
+    SetSettlementMode {
+        mode: SettlementMode,
+    },
+    /// Enables or disables the epoch-boundary deposit queue
+    SetDepositQueue {
+        enabled: bool,
+    },
+    /// Two-step admin rotation: new_authority accepts via accept_authority
+    TransferAuthority {
+        new_authority: Pubkey,
+    },
+    /// Split / reverse split of the underlying: new_multiplier is UI tokens per
+    /// raw token (PRICE_PER_SHARE_SCALE), e.g. doubled for a 2:1 split of a
+    /// scaled-amount Token-2022 xStock. Executes once the mint's ScaledUiAmount
+    /// multiplier matches; settlement is blocked until then
+    CorporateAction {
+        new_multiplier: u64,
+    },
+}
+
+impl AdminAction {
+    /// Checks that do not depend on vault state, enforced when queuing
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            AdminAction::AddMarketMaker { notional_limit, .. } => {
+                require!(*notional_limit > 0, VaultError::InvalidParameter)
+            }
+            AdminAction::ParamChange {
+                utilization_cap_bps: Some(cap),
+                ..
+            } => require!(*cap <= 10000, VaultError::InvalidParameter),
+            AdminAction::SetFeeConfig {
+                management_fee_bps,
+                performance_fee_bps,
+                deposit_fee_bps,
+                withdrawal_fee_bps,
+                ..
+            } => require!(
+                *management_fee_bps <= MAX_MANAGEMENT_FEE_BPS
+                    && *performance_fee_bps <= MAX_PERFORMANCE_FEE_BPS
+                    && *deposit_fee_bps <= MAX_FLOW_FEE_BPS
+                    && *withdrawal_fee_bps <= MAX_FLOW_FEE_BPS,
+                VaultError::FeeTooHigh
+            ),
+            AdminAction::SetTimelockDelay { delay } => require!(
+                (MIN_TIMELOCK_DELAY..=MAX_TIMELOCK_DELAY).contains(delay),
+                VaultError::InvalidParameter
+            ),
+            AdminAction::SetCompoundConfig { compound_bps, .. } => {
+                require!(*compound_bps <= 10000, VaultError::InvalidCompoundConfig)
+            }
+            AdminAction::SetOracleConfig {
+                max_price_staleness,
+                max_confidence_bps,
+                ..
+            } => require!(
+                *max_price_staleness > 0 && *max_confidence_bps > 0 && *max_confidence_bps <= 10000,
+                VaultError::InvalidParameter
+            ),
+            AdminAction::TransferAuthority { new_authority } => require!(
+                *new_authority != Pubkey::default(),
+                VaultError::InvalidParameter
+            ),
+            AdminAction::CorporateAction { new_multiplier } => {
+                require!(*new_multiplier > 0, VaultError::InvalidParameter)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MarketMakerStatus {
+    /// Can open new positions
+    Active,
+    /// Removed from the whitelist; existing positions still settle
+    Removed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DividendTreatment {
+    /// Added to the vault's assets, raising price-per-share
+    Reinvest,
+    /// Credited to the premium-per-share index, claimable via claim_premium
+    Distribute,
+}
+
+/// Market maker quote signed off-chain and verified via the Ed25519 program
+/// The signed message is the Borsh serialization of this struct
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RfqQuote {
+    pub rfq_id: u64,
+    pub vault: Pubkey,
+    /// Strike in premium_mint base units per whole underlying token
+    pub strike_price: u64,
+    pub expiry: i64,
+    /// Notional in underlying tokens
+    pub notional: u64,
+    /// Premium in premium_mint tokens
+    pub premium: u64,
+    /// Quote is invalid after this timestamp
+    pub deadline: i64,
+}
+
+/// Marks a signed quote as filled (prevents replay)
+#[account]
+#[derive(InitSpace)]
+pub struct QuoteReceipt {
+    pub vault: Pubkey,
+    pub market_maker: Pubkey,
+    pub rfq_id: u64,
+    pub position: Pubkey,
+    pub bump: u8,
+}
+
+/// Admin action waiting out the governance timelock
+#[account]
+#[derive(InitSpace)]
+pub struct PendingAction {
+    pub vault: Pubkey,
+    pub id: u64,
+    pub action: AdminAction,
+    pub queued_at: i64,
+    pub executable_at: i64,
+    pub bump: u8,
+}
+
+/// Per-counterparty whitelist entry, limits and stats
+/// Amounts are in collateral units (underlying for calls, premium_mint for puts)
+/// except total_premium_paid, which is in premium_mint
+#[account]
+#[derive(InitSpace)]
+pub struct MarketMakerAccount {
+    pub vault: Pubkey,
+    pub market_maker: Pubkey,
+    pub status: MarketMakerStatus,
+    /// Maximum outstanding_exposure across open positions
+    pub notional_limit: u64,
+    pub outstanding_exposure: u64,
+    /// Strike value of open positions in premium_mint, backed by
+    /// MAKER_COLLATERAL_BPS of collateral
+    pub outstanding_notional_value: u64,
+    pub total_premium_paid: u64,
+    pub total_settlement_received: u64,
+    /// premium_mint held in the maker_collateral escrow for this maker
+    pub collateral: u64,
+    /// Premium owed for fills recorded via record_notional_exposure
+    pub unpaid_premium: u64,
+    /// Collateral is slashable after this time while premium is unpaid
+    pub premium_due_at: i64,
+    pub bump: u8,
+}
+
+impl MarketMakerAccount {
+    pub fn record_premium_paid(&mut self, amount: u64) -> Result<()> {
+        self.total_premium_paid = self
+            .total_premium_paid
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+
+    /// Collateral backing unpaid premium and open positions
+    pub fn required_collateral(&self) -> Result<u64> {
+        bps_of(self.outstanding_notional_value, MAKER_COLLATERAL_BPS)?
+            .checked_add(self.unpaid_premium)
+            .ok_or(VaultError::Overflow.into())
+    }
+
+    pub fn check_collateral(&self) -> Result<()> {
+        require!(
+            self.collateral >= self.required_collateral()?,
+            VaultError::InsufficientMakerCollateral
+        );
+        Ok(())
+    }
+
+    /// Release a closed position's exposure and record what the maker received
+    /// Saturates: positions opened before per-maker tracking recorded no exposure
+    pub fn close_position(
+        &mut self,
+        exposure: u64,
+        notional_value: u64,
+        settlement_received: u64,
+    ) -> Result<()> {
+        self.outstanding_exposure = self.outstanding_exposure.saturating_sub(exposure);
+        self.outstanding_notional_value = self
+            .outstanding_notional_value
+            .saturating_sub(notional_value);
+        self.total_settlement_received = self
+            .total_settlement_received
+            .checked_add(settlement_received)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Market maker list used before per-maker accounts; read and closed by
+/// migrate_whitelist
+#[account]
+pub struct VaultWhitelist {
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub market_makers: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+// ============================================================================
+// Contexts
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(asset_id: String, utilization_cap_bps: u16, min_epoch_duration: i64, strategy: Strategy)]
+pub struct InitializeVault<'info> {
+    #[account(
+        init,
+        payer = authority,
+        // Space: 8 (discriminator) + Vault::INIT_SPACE (includes reserved padding for
+        // future fields; see migrate_vault)
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", asset_id.as_bytes()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mint::token_program = underlying_token_program)]
+    pub underlying_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = premium_token_program)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = match strategy {
+            Strategy::CoveredCall => underlying_mint.decimals,
+            Strategy::CashSecuredPut => premium_mint.decimals,
+        },
+        mint::authority = vault,
+        mint::token_program = token_program,
+        extensions::transfer_hook::authority = vault,
+        extensions::transfer_hook::program_id = crate::ID,
+        seeds = [b"share_mint", vault.key().as_ref()],
+        bump
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Transfer hook ExtraAccountMetaList of the share mint, written
+    /// by the handler
+    #[account(
+        init,
+        payer = authority,
+        space = ExtraAccountMetaList::size_of(SHARE_HOOK_EXTRA_ACCOUNTS)?,
+        seeds = [b"extra-account-metas", share_mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = underlying_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = underlying_token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = premium_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = premium_token_program,
+    )]
+    pub premium_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    /// Token program of the share mint (Token-2022, for the transfer hook)
+    pub token_program: Program<'info, Token2022>,
+    /// Token program of the underlying mint (SPL Token or Token-2022)
+    pub underlying_token_program: Interface<'info, TokenInterface>,
+    /// Token program of the premium mint (SPL Token or Token-2022)
+    pub premium_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        address = vault.share_mint
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral account (vault_token_account or premium_token_account for put vaults)
+    #[account(
+        mut,
+        address = vault.collateral_token_account()
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.collateral_mint(),
+        constraint = user_token_account.key() != vault_token_account.key() @ VaultError::DuplicateAccount
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.share_mint
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Fee recipient's share account (required when the fee is non-zero)
+    #[account(
+        mut,
+        token::mint = vault.share_mint,
+        token::authority = vault.fee_recipient
+    )]
+    pub fee_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// PremiumCheckpoint of fee_share_account (required with it)
+    #[account(mut)]
+    pub fee_premium_checkpoint: Option<Account<'info, PremiumCheckpoint>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Required when the vault has an attester
+    #[account(
+        seeds = [b"attestation", vault.key().as_ref(), user.key().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Option<Account<'info, Attestation>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserDeposits::INIT_SPACE,
+        seeds = [b"user_deposits", vault.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_deposits: Account<'info, UserDeposits>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + PremiumCheckpoint::INIT_SPACE,
+        seeds = [b"premium_checkpoint", vault.key().as_ref(), user_share_account.key().as_ref()],
+        bump
+    )]
+    pub premium_checkpoint: Account<'info, PremiumCheckpoint>,
+
+    pub system_program: Program<'info, System>,
+    #[account(address = vault.collateral_mint())]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.collateral_token_program())]
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RequestDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + DepositRequest::INIT_SPACE,
+        seeds = [b"deposit", vault.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub deposit_request: Account<'info, DepositRequest>,
+
+    /// Collateral account (vault_token_account or premium_token_account for put vaults)
+    #[account(
+        mut,
+        address = vault.collateral_token_account()
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.collateral_mint(),
+        constraint = user_token_account.key() != vault_token_account.key() @ VaultError::DuplicateAccount
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Required when the vault has an attester
+    #[account(
+        seeds = [b"attestation", vault.key().as_ref(), user.key().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Option<Account<'info, Attestation>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserDeposits::INIT_SPACE,
+        seeds = [b"user_deposits", vault.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_deposits: Account<'info, UserDeposits>,
+
+    pub system_program: Program<'info, System>,
+    #[account(address = vault.collateral_mint())]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.collateral_token_program())]
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDeposit<'info> {
+    #[account(
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"deposit", vault.key().as_ref(), user.key().as_ref()],
+        bump = deposit_request.bump,
+        has_one = user,
+        has_one = vault
+    )]
+    pub deposit_request: Account<'info, DepositRequest>,
+
+    /// Snapshot of the roll that admitted the deposit, closing request_epoch
+    #[account(
+        seeds = [
+            b"epoch_snapshot",
+            vault.key().as_ref(),
+            &deposit_request.request_epoch.to_le_bytes()
+        ],
+        bump = epoch_snapshot.bump
+    )]
+    pub epoch_snapshot: Account<'info, EpochSnapshot>,
+
+    #[account(
+        mut,
+        address = vault.share_mint
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = vault.share_mint
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Fee recipient's share account (required when the fee is non-zero)
+    #[account(
+        mut,
+        token::mint = vault.share_mint,
+        token::authority = vault.fee_recipient
+    )]
+    pub fee_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// PremiumCheckpoint of fee_share_account (required with it)
+    #[account(mut)]
+    pub fee_premium_checkpoint: Option<Account<'info, PremiumCheckpoint>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + PremiumCheckpoint::INIT_SPACE,
+        seeds = [b"premium_checkpoint", vault.key().as_ref(), user_share_account.key().as_ref()],
+        bump
+    )]
+    pub premium_checkpoint: Account<'info, PremiumCheckpoint>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + WithdrawalRequest::INIT_SPACE,
+        seeds = [b"withdrawal", vault.key().as_ref(), user.key().as_ref(), &vault.epoch.to_le_bytes()],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    #[account(
+        mut,
+        token::mint = vault.share_mint
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + PremiumCheckpoint::INIT_SPACE,
+        seeds = [b"premium_checkpoint", vault.key().as_ref(), user_share_account.key().as_ref()],
+        bump
+    )]
+    pub premium_checkpoint: Account<'info, PremiumCheckpoint>,
+
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        address = vault.share_mint
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        close = user,
+        has_one = user,
+        has_one = vault,
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    #[account(
+        mut,
+        token::mint = vault.share_mint
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + PremiumCheckpoint::INIT_SPACE,
+        seeds = [b"premium_checkpoint", vault.key().as_ref(), user_share_account.key().as_ref()],
+        bump
+    )]
+    pub premium_checkpoint: Account<'info, PremiumCheckpoint>,
+
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        address = vault.share_mint
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPremium<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        token::mint = vault.share_mint,
+        token::authority = owner
+    )]
+    pub owner_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = vault.premium_token_account
+    )]
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.premium_mint,
+        constraint = owner_premium_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
+    )]
+    pub owner_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + PremiumCheckpoint::INIT_SPACE,
+        seeds = [b"premium_checkpoint", vault.key().as_ref(), owner_share_account.key().as_ref()],
+        bump
+    )]
+    pub premium_checkpoint: Account<'info, PremiumCheckpoint>,
+
+    pub system_program: Program<'info, System>,
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct OpenPremiumCheckpoint<'info> {
+    #[account(
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(token::mint = vault.share_mint)]
+    pub share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PremiumCheckpoint::INIT_SPACE,
+        seeds = [b"premium_checkpoint", vault.key().as_ref(), share_account.key().as_ref()],
+        bump
+    )]
+    pub premium_checkpoint: Account<'info, PremiumCheckpoint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts of the spl-transfer-hook-interface Execute instruction, in order;
+/// vault, both checkpoints and the attestation are resolved from the
+/// ExtraAccountMetaList
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    #[account(token::mint = share_mint)]
+    pub source_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(token::mint = share_mint)]
+    pub destination_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Source owner or delegate, verified by Token-2022
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: ExtraAccountMetaList of the share mint
+    #[account(
+        seeds = [b"extra-account-metas", share_mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"premium_checkpoint", vault.key().as_ref(), source_share_account.key().as_ref()],
+        bump = source_checkpoint.bump
+    )]
+    pub source_checkpoint: Account<'info, PremiumCheckpoint>,
+
+    /// The destination must have opened a checkpoint (open_premium_checkpoint)
+    #[account(
+        mut,
+        seeds = [b"premium_checkpoint", vault.key().as_ref(), destination_share_account.key().as_ref()],
+        bump = destination_checkpoint.bump
+    )]
+    pub destination_checkpoint: Account<'info, PremiumCheckpoint>,
+
+    /// CHECK: Attestation PDA of the destination's owner; may be uninitialized
+    /// and is only read when the vault has an attester
+    #[account(
+        seeds = [b"attestation", vault.key().as_ref(), destination_share_account.owner.as_ref()],
+        bump
+    )]
+    pub destination_attestation: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        has_one = user,
+        has_one = vault,
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    /// Snapshot of the roll the shares exited at, closing request_epoch
+    #[account(
+        seeds = [
+            b"epoch_snapshot",
+            vault.key().as_ref(),
+            &withdrawal_request.request_epoch.to_le_bytes()
+        ],
+        bump = epoch_snapshot.bump
+    )]
+    pub epoch_snapshot: Account<'info, EpochSnapshot>,
+
+    #[account(
+        mut,
+        address = vault.share_mint
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = vault.vault_token_account
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        constraint = user_token_account.key() != vault_token_account.key() @ VaultError::DuplicateAccount
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = vault.premium_token_account
+    )]
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = premium_mint,
+        associated_token::authority = user,
+        associated_token::token_program = premium_token_program,
+        constraint = user_premium_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
+    )]
+    pub user_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    /// Fee recipient's share account (required when the fee is non-zero)
+    #[account(
+        mut,
+        token::mint = vault.share_mint,
+        token::authority = vault.fee_recipient
+    )]
+    pub fee_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// PremiumCheckpoint of fee_share_account (required with it)
+    #[account(mut)]
+    pub fee_premium_checkpoint: Option<Account<'info, PremiumCheckpoint>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_deposits", vault.key().as_ref(), user.key().as_ref()],
+        bump = user_deposits.bump
+    )]
+    pub user_deposits: Option<Account<'info, UserDeposits>>,
+
+    #[account(address = vault.underlying_mint)]
+    pub underlying_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Program<'info, Token2022>,
+    #[account(address = vault.underlying_token_program)]
+    pub underlying_token_program: Interface<'info, TokenInterface>,
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct VaultWhitelist {
-    pub authority: Pubkey,
-    pub vault: Pubkey,
-    pub market_makers: Vec<Pubkey>,
-    pub bump: u8,
+#[derive(Accounts)]
+pub struct AdvanceEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = keeper
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        address = vault.share_mint
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = vault.premium_token_account
+    )]
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Fee recipient's share account (required when a management fee accrues)
+    #[account(
+        mut,
+        token::mint = vault.share_mint,
+        token::authority = vault.fee_recipient
+    )]
+    pub fee_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// PremiumCheckpoint of fee_share_account (required with it)
+    #[account(mut)]
+    pub fee_premium_checkpoint: Option<Account<'info, PremiumCheckpoint>>,
+
+    /// Fee recipient's premium account (required when a performance fee is charged)
+    #[account(
+        mut,
+        token::mint = vault.premium_mint,
+        token::authority = vault.fee_recipient,
+        constraint = fee_premium_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
+    )]
+    pub fee_premium_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = keeper,
+        space = 8 + EpochSnapshot::INIT_SPACE,
+        seeds = [b"epoch_snapshot", vault.key().as_ref(), &vault.epoch.to_le_bytes()],
+        bump
+    )]
+    pub epoch_snapshot: Account<'info, EpochSnapshot>,
+
+    /// Settlement of the epoch being closed (required if it sold any options)
+    #[account(
+        seeds = [b"epoch_settlement", vault.key().as_ref(), &vault.epoch.to_le_bytes()],
+        bump = epoch_settlement.bump,
+        has_one = vault
+    )]
+    pub epoch_settlement: Option<Account<'info, EpochSettlement>>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Program<'info, Token2022>,
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CompoundPremium<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = keeper
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        address = vault.premium_token_account
+    )]
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = vault.vault_token_account
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA delegate for the swap allowance; holds no data
+    #[account(
+        seeds = [b"swap_authority", vault.key().as_ref()],
+        bump
+    )]
+    pub swap_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Swap program configured by the authority
+    #[account(
+        executable,
+        address = vault.swap_program
+    )]
+    pub swap_program: UncheckedAccount<'info>,
+
+    pub keeper: Signer<'info>,
+
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = vault.underlying_mint)]
+    pub underlying_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
+    #[account(address = vault.underlying_token_program)]
+    pub underlying_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(notional_tokens: u64, premium: u64, strike_price: u64, market_maker: Pubkey)]
+pub struct RecordNotionalExposure<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = keeper
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"market_maker", vault.key().as_ref(), market_maker.as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
+    )]
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + EpochSettlement::INIT_SPACE,
+        seeds = [b"epoch_settlement", vault.key().as_ref(), &vault.epoch.to_le_bytes()],
+        bump
+    )]
+    pub epoch_settlement: Account<'info, EpochSettlement>,
+
+    #[account(
+        init,
+        payer = keeper,
+        space = 8 + OptionPosition::INIT_SPACE,
+        seeds = [
+            b"position",
+            vault.key().as_ref(),
+            &vault.epoch.to_le_bytes(),
+            &epoch_settlement.position_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub position: Account<'info, OptionPosition>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(address = vault.underlying_mint)]
+    pub underlying_mint: InterfaceAccount<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FillRfq<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = keeper
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"market_maker", vault.key().as_ref(), market_maker.key().as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
+    )]
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + EpochSettlement::INIT_SPACE,
+        seeds = [b"epoch_settlement", vault.key().as_ref(), &vault.epoch.to_le_bytes()],
+        bump
+    )]
+    pub epoch_settlement: Account<'info, EpochSettlement>,
+
+    #[account(
+        init,
+        payer = keeper,
+        space = 8 + OptionPosition::INIT_SPACE,
+        seeds = [
+            b"position",
+            vault.key().as_ref(),
+            &vault.epoch.to_le_bytes(),
+            &epoch_settlement.position_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub position: Account<'info, OptionPosition>,
+
+    #[account(
+        mut,
+        address = vault.premium_token_account
+    )]
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.premium_mint,
+        token::authority = market_maker,
+        constraint = market_maker_token_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
+    )]
+    pub market_maker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Whitelisted market maker paying the premium
+    pub market_maker: Signer<'info>,
+
+    /// Vault keeper co-signs to confirm the RFQ terms
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.underlying_mint)]
+    pub underlying_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
 }
 
-// ============================================================================
-// Contexts
-// ============================================================================
+#[derive(Accounts)]
+#[instruction(quote: RfqQuote)]
+pub struct FillSignedQuote<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = keeper
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"market_maker", vault.key().as_ref(), market_maker.key().as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
+    )]
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + EpochSettlement::INIT_SPACE,
+        seeds = [b"epoch_settlement", vault.key().as_ref(), &vault.epoch.to_le_bytes()],
+        bump
+    )]
+    pub epoch_settlement: Account<'info, EpochSettlement>,
 
-#[derive(Accounts)]
-#[instruction(asset_id: String)]
-pub struct InitializeVault<'info> {
     #[account(
         init,
-        payer = authority,
-        // Space: 8 (discriminator) + 32 (authority) + 68 (asset_id string max) 
-        //        + 32*6 (mints and accounts) + 8*8 (u64 fields) + 2 + 8 + 4 + 1 (is_paused)
-        //        + 8 (pending_min_epoch_duration) + 2 (pending_utilization_cap) + 8 (param_change_unlock_time) + 1 (bump)
-        space = 8 + 32 + 68 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 2 + 8 + 8 + 8 + 8 + 4 + 8 + 1 + 8 + 2 + 8 + 1,
-        seeds = [b"vault", asset_id.as_bytes()],
+        payer = keeper,
+        space = 8 + OptionPosition::INIT_SPACE,
+        seeds = [
+            b"position",
+            vault.key().as_ref(),
+            &vault.epoch.to_le_bytes(),
+            &epoch_settlement.position_count.to_le_bytes()
+        ],
         bump
     )]
-    pub vault: Account<'info, Vault>,
+    pub position: Account<'info, OptionPosition>,
 
-    pub underlying_mint: Account<'info, Mint>,
-    pub premium_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        address = vault.premium_token_account
+    )]
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.premium_mint,
+        token::authority = market_maker,
+        constraint = market_maker_token_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
+    )]
+    pub market_maker_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init,
-        payer = authority,
-        mint::decimals = underlying_mint.decimals,
-        mint::authority = vault,
-        seeds = [b"share_mint", vault.key().as_ref()],
+        payer = keeper,
+        space = 8 + QuoteReceipt::INIT_SPACE,
+        seeds = [
+            b"quote",
+            vault.key().as_ref(),
+            market_maker.key().as_ref(),
+            &quote.rfq_id.to_le_bytes()
+        ],
         bump
     )]
-    pub share_mint: Account<'info, Mint>,
+    pub quote_receipt: Account<'info, QuoteReceipt>,
+
+    /// CHECK: Whitelisted market maker; quote signature verified via Ed25519 introspection
+    pub market_maker: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
 
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.underlying_mint)]
+    pub underlying_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleEpoch<'info> {
     #[account(
-        init,
-        payer = authority,
-        associated_token::mint = underlying_mint,
-        associated_token::authority = vault,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = settlement_operator
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault: Account<'info, Vault>,
 
     #[account(
-        init,
-        payer = authority,
-        associated_token::mint = premium_mint,
-        associated_token::authority = vault,
+        mut,
+        seeds = [b"epoch_settlement", vault.key().as_ref(), &vault.epoch.to_le_bytes()],
+        bump = epoch_settlement.bump,
+        has_one = vault
     )]
-    pub premium_token_account: Account<'info, TokenAccount>,
+    pub epoch_settlement: Account<'info, EpochSettlement>,
+
+    /// CHECK: Pyth PriceUpdateV2 account, validated in load_oracle_price
+    pub price_update: UncheckedAccount<'info>,
+
+    #[account(address = vault.underlying_mint)]
+    pub underlying_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
 
+    pub settlement_operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositMakerCollateral<'info> {
     #[account(
-        init,
-        payer = authority,
-        token::mint = share_mint,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"market_maker", vault.key().as_ref(), market_maker.key().as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
+    )]
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
+
+    /// Escrow holding every maker's collateral, owned by the vault PDA
+    #[account(
+        init_if_needed,
+        payer = market_maker,
+        token::mint = premium_mint,
         token::authority = vault,
-        seeds = [b"share_escrow", vault.key().as_ref()],
+        token::token_program = premium_token_program,
+        seeds = [b"maker_collateral", vault.key().as_ref()],
         bump
     )]
-    pub share_escrow: Account<'info, TokenAccount>,
+    pub maker_collateral_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.premium_mint,
+        token::authority = market_maker
+    )]
+    pub market_maker_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub market_maker: Signer<'info>,
+
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
 
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct Deposit<'info> {
+pub struct WithdrawMakerCollateral<'info> {
     #[account(
-        mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
-        bump = vault.bump
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
     )]
     pub vault: Account<'info, Vault>,
 
     #[account(
         mut,
-        address = vault.share_mint
+        seeds = [b"market_maker", vault.key().as_ref(), market_maker.key().as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
     )]
-    pub share_mint: Account<'info, Mint>,
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
 
     #[account(
         mut,
-        address = vault.vault_token_account
+        seeds = [b"maker_collateral", vault.key().as_ref()],
+        bump
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub maker_collateral_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        token::mint = vault.underlying_mint,
-        constraint = user_token_account.key() != vault_token_account.key() @ VaultError::DuplicateAccount
+        token::mint = vault.premium_mint,
+        token::authority = market_maker
+    )]
+    pub market_maker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub market_maker: Signer<'info>,
+
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct PayPremium<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub vault: Account<'info, Vault>,
 
     #[account(
         mut,
-        token::mint = vault.share_mint,
-        constraint = user_share_account.key() != share_escrow.key() @ VaultError::DuplicateAccount
+        seeds = [b"market_maker", vault.key().as_ref(), market_maker.key().as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
     )]
-    pub user_share_account: Account<'info, TokenAccount>,
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
 
     #[account(
         mut,
-        address = vault.share_escrow
+        address = vault.premium_token_account
     )]
-    pub share_escrow: Account<'info, TokenAccount>,
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub user: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = vault.premium_mint,
+        token::authority = market_maker
+    )]
+    pub market_maker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub market_maker: Signer<'info>,
+
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
 
-    pub token_program: Program<'info, Token>,
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct RequestWithdrawal<'info> {
+pub struct SlashMakerCollateral<'info> {
     #[account(
         mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
-        bump = vault.bump
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = keeper
     )]
     pub vault: Account<'info, Vault>,
 
     #[account(
-        init,
-        payer = user,
-        space = 8 + 32 + 32 + 8 + 8 + 1,
-        seeds = [b"withdrawal", vault.key().as_ref(), user.key().as_ref(), &vault.epoch.to_le_bytes()],
+        mut,
+        seeds = [b"market_maker", vault.key().as_ref(), market_maker_account.market_maker.as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
+    )]
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"maker_collateral", vault.key().as_ref()],
         bump
     )]
-    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    pub maker_collateral_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = vault.premium_token_account
+    )]
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub keeper: Signer<'info>,
+
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CollectPremium<'info> {
+    #[account(
+        mut,  // SECURITY FIX M-1b: Mutable to track premium state
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = keeper  // SECURITY FIX M-1: Require keeper to prevent front-running
+    )]
+    pub vault: Account<'info, Vault>,
 
     #[account(
         mut,
-        address = vault.share_escrow
+        address = vault.premium_token_account
     )]
-    pub share_escrow: Account<'info, TokenAccount>,
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        token::mint = vault.share_mint
+        token::mint = vault.premium_mint,
+        // SECURITY: Prevent duplicate account attack
+        constraint = payer_token_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
     )]
-    pub user_share_account: Account<'info, TokenAccount>,
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub payer: Signer<'info>,
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// SECURITY FIX M-1: Keeper must sign to prevent front-running premium collection
+    pub keeper: Signer<'info>,
+
+    #[account(address = vault.premium_mint)]
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct ProcessWithdrawal<'info> {
+pub struct PaySettlement<'info> {
     #[account(
         mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
-        bump = vault.bump
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = settlement_operator,
+        has_one = keeper
     )]
     pub vault: Account<'info, Vault>,
 
     #[account(
         mut,
-        has_one = user,
+        seeds = [b"market_maker", vault.key().as_ref(), recipient.key().as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
+    )]
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch_settlement", vault.key().as_ref(), &position.epoch.to_le_bytes()],
+        bump = epoch_settlement.bump,
+        has_one = vault
+    )]
+    pub epoch_settlement: Account<'info, EpochSettlement>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            vault.key().as_ref(),
+            &position.epoch.to_le_bytes(),
+            &position.index.to_le_bytes()
+        ],
+        bump = position.bump,
         has_one = vault,
+        constraint = position.counterparty == recipient.key() @ VaultError::InvalidCounterparty,
+        close = keeper
     )]
-    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    pub position: Account<'info, OptionPosition>,
 
     #[account(
         mut,
-        address = vault.share_mint
+        address = vault.collateral_token_account()
     )]
-    pub share_mint: Account<'info, Mint>,
+    pub vault_collateral_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        address = vault.vault_token_account
+        token::mint = vault.collateral_mint(),
+        token::authority = recipient,
+        constraint = recipient_token_account.key() != vault_collateral_account.key() @ VaultError::DuplicateAccount
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    /// CHECK: Position counterparty with a MarketMakerAccount
+    pub recipient: AccountInfo<'info>,
+
+    pub settlement_operator: Signer<'info>,
+
+    /// CHECK: Vault keeper, paid the position's rent and receives it back
+    #[account(mut)]
+    pub keeper: UncheckedAccount<'info>,
+
+    #[account(address = vault.collateral_mint())]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.collateral_token_program())]
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ExercisePosition<'info> {
     #[account(
         mut,
-        token::mint = vault.underlying_mint,
-        constraint = user_token_account.key() != vault_token_account.key() @ VaultError::DuplicateAccount
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = keeper
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"market_maker", vault.key().as_ref(), market_maker.key().as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
+    )]
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch_settlement", vault.key().as_ref(), &position.epoch.to_le_bytes()],
+        bump = epoch_settlement.bump,
+        has_one = vault
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub epoch_settlement: Account<'info, EpochSettlement>,
 
     #[account(
         mut,
-        address = vault.share_escrow
+        seeds = [
+            b"position",
+            vault.key().as_ref(),
+            &position.epoch.to_le_bytes(),
+            &position.index.to_le_bytes()
+        ],
+        bump = position.bump,
+        has_one = vault,
+        constraint = position.counterparty == market_maker.key() @ VaultError::InvalidCounterparty,
+        close = keeper
+    )]
+    pub position: Account<'info, OptionPosition>,
+
+    #[account(
+        mut,
+        address = vault.vault_token_account
     )]
-    pub share_escrow: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         address = vault.premium_token_account
     )]
-    pub vault_premium_account: Account<'info, TokenAccount>,
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        associated_token::mint = premium_mint,
-        associated_token::authority = user,
-        constraint = user_premium_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
+        token::mint = vault.underlying_mint,
+        token::authority = market_maker,
+        constraint = market_maker_token_account.key() != vault_token_account.key() @ VaultError::DuplicateAccount
+    )]
+    pub market_maker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.premium_mint,
+        token::authority = market_maker,
+        constraint = market_maker_premium_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
     )]
-    pub user_premium_account: Account<'info, TokenAccount>,
+    pub market_maker_premium_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub market_maker: Signer<'info>,
+
+    /// CHECK: Vault keeper, paid the position's rent and receives it back
+    #[account(mut)]
+    pub keeper: UncheckedAccount<'info>,
+
+    #[account(address = vault.underlying_mint)]
+    pub underlying_mint: InterfaceAccount<'info, Mint>,
 
     #[account(address = vault.premium_mint)]
-    pub premium_mint: Account<'info, Mint>,
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = vault.underlying_token_program)]
+    pub underlying_token_program: Interface<'info, TokenInterface>,
+    #[account(address = vault.premium_token_program)]
+    pub premium_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateWhitelist<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = authority
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"whitelist", vault.key().as_ref()],
+        bump = whitelist.bump,
+        has_one = vault,
+        close = authority
+    )]
+    pub whitelist: Account<'info, VaultWhitelist>,
 
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub authority: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+/// SECURITY FIX M-4: Context for removing market makers
 #[derive(Accounts)]
-pub struct AdvanceEpoch<'info> {
+#[instruction(market_maker: Pubkey)]
+pub struct RemoveMarketMaker<'info> {
     #[account(
-        mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
         has_one = authority
     )]
     pub vault: Account<'info, Vault>,
 
+    #[account(
+        mut,
+        seeds = [b"market_maker", vault.key().as_ref(), market_maker.as_ref()],
+        bump = market_maker_account.bump,
+        has_one = vault
+    )]
+    pub market_maker_account: Account<'info, MarketMakerAccount>,
+
     pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct RecordNotionalExposure<'info> {
+pub struct QueueAction<'info> {
     #[account(
         mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
         has_one = authority
     )]
-    pub vault: Account<'info, Vault>,
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingAction::INIT_SPACE,
+        seeds = [b"pending_action", vault.key().as_ref(), &vault.next_action_id.to_le_bytes()],
+        bump
+    )]
+    pub pending_action: Account<'info, PendingAction>,
 
+    #[account(mut)]
     pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CollectPremium<'info> {
+pub struct ExecuteAction<'info> {
     #[account(
-        mut,  // SECURITY FIX M-1b: Mutable to track premium state
+        mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
-        has_one = authority  // SECURITY FIX M-1: Require authority to prevent front-running
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = authority
     )]
     pub vault: Account<'info, Vault>,
 
     #[account(
         mut,
-        address = vault.premium_token_account
+        seeds = [b"pending_action", vault.key().as_ref(), &pending_action.id.to_le_bytes()],
+        bump = pending_action.bump,
+        has_one = vault,
+        close = authority
     )]
-    pub vault_premium_account: Account<'info, TokenAccount>,
+    pub pending_action: Account<'info, PendingAction>,
 
+    /// CHECK: Maker named by an AddMarketMaker action (checked in the handler)
+    pub market_maker: Option<UncheckedAccount<'info>>,
+
+    /// Required for AddMarketMaker; created on first add
     #[account(
-        mut,
-        token::mint = vault.premium_mint,
-        // SECURITY: Prevent duplicate account attack
-        constraint = payer_token_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
+        init_if_needed,
+        payer = authority,
+        space = 8 + MarketMakerAccount::INIT_SPACE,
+        seeds = [
+            b"market_maker",
+            vault.key().as_ref(),
+            market_maker.as_ref().map(|m| m.key()).unwrap_or_default().as_ref()
+        ],
+        bump
     )]
-    pub payer_token_account: Account<'info, TokenAccount>,
+    pub market_maker_account: Option<Account<'info, MarketMakerAccount>>,
 
-    #[account(mut)]
-    pub payer: Signer<'info>,
+    /// Required for CorporateAction
+    #[account(address = vault.underlying_mint)]
+    pub underlying_mint: Option<InterfaceAccount<'info, Mint>>,
 
-    /// SECURITY FIX M-1: Authority must sign to prevent front-running premium collection
+    #[account(mut)]
     pub authority: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct PaySettlement<'info> {
+pub struct CancelAction<'info> {
     #[account(
+        mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
         has_one = authority
     )]
     pub vault: Account<'info, Vault>,
 
-    #[account(
-        seeds = [b"whitelist", vault.key().as_ref()],
-        bump = whitelist.bump,
-        has_one = vault
-    )]
-    pub whitelist: Account<'info, VaultWhitelist>,
-
-    #[account(
-        mut,
-        address = vault.premium_token_account
-    )]
-    pub vault_premium_account: Account<'info, TokenAccount>,
-
     #[account(
         mut,
-        token::mint = vault.premium_mint,
-        constraint = recipient_token_account.key() != vault_premium_account.key() @ VaultError::DuplicateAccount
+        seeds = [b"pending_action", vault.key().as_ref(), &pending_action.id.to_le_bytes()],
+        bump = pending_action.bump,
+        has_one = vault,
+        close = authority
     )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
-
-    /// CHECK: Recipient must be in the whitelist
-    pub recipient: AccountInfo<'info>,
+    pub pending_action: Account<'info, PendingAction>,
 
+    #[account(mut)]
     pub authority: Signer<'info>,
-
-    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeWhitelist<'info> {
+#[instruction(wallet: Pubkey)]
+pub struct IssueAttestation<'info> {
     #[account(
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
-        has_one = authority
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = attester
     )]
     pub vault: Account<'info, Vault>,
 
     #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 32 + (4 + 32 * 10) + 1, // Max 10 MMs
-        seeds = [b"whitelist", vault.key().as_ref()],
+        init_if_needed,
+        payer = attester,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [b"attestation", vault.key().as_ref(), wallet.as_ref()],
         bump
     )]
-    pub whitelist: Account<'info, VaultWhitelist>,
+    pub attestation: Account<'info, Attestation>,
 
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub attester: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AddMarketMaker<'info> {
+#[instruction(wallet: Pubkey)]
+pub struct RevokeAttestation<'info> {
     #[account(
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
-        has_one = authority
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        has_one = attester
     )]
     pub vault: Account<'info, Vault>,
 
     #[account(
         mut,
-        seeds = [b"whitelist", vault.key().as_ref()],
-        bump = whitelist.bump,
-        has_one = vault,
-        has_one = authority
+        seeds = [b"attestation", vault.key().as_ref(), wallet.as_ref()],
+        bump = attestation.bump,
+        close = attester
     )]
-    pub whitelist: Account<'info, VaultWhitelist>,
+    pub attestation: Account<'info, Attestation>,
 
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub attester: Signer<'info>,
 }
 
-/// SECURITY FIX M-4: Context for removing market makers
 #[derive(Accounts)]
-pub struct RemoveMarketMaker<'info> {
+pub struct AcceptAuthority<'info> {
     #[account(
+        mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
-        has_one = authority
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        constraint = vault.pending_authority == new_authority.key() @ VaultError::NotPendingAuthority
     )]
     pub vault: Account<'info, Vault>,
 
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
     #[account(
         mut,
-        seeds = [b"whitelist", vault.key().as_ref()],
-        bump = whitelist.bump,
-        has_one = vault,
-        has_one = authority
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        constraint = caller.key() == vault.authority
+            || caller.key() == vault.guardian @ VaultError::MissingRole
     )]
-    pub whitelist: Account<'info, VaultWhitelist>,
+    pub vault: Account<'info, Vault>,
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    /// Admin authority or guardian
+    pub caller: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct SetPause<'info> {
+pub struct SetParam<'info> {
     #[account(
         mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
         has_one = authority
     )]
     pub vault: Account<'info, Vault>,
@@ -1395,15 +6447,24 @@ pub struct SetPause<'info> {
 }
 
 #[derive(Accounts)]
-pub struct SetParam<'info> {
+pub struct RecordDividend<'info> {
     #[account(
         mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
         has_one = authority
     )]
     pub vault: Account<'info, Vault>,
 
+    /// Vault token account the dividend was paid into
+    #[account(
+        constraint = dividend_account.key() == vault.vault_token_account
+            || dividend_account.key() == vault.premium_token_account
+            @ VaultError::InvalidDividendTreatment
+    )]
+    pub dividend_account: InterfaceAccount<'info, TokenAccount>,
+
     pub authority: Signer<'info>,
 }
 
@@ -1413,6 +6474,7 @@ pub struct ReconcilePremiumBalance<'info> {
         mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
         has_one = authority
     )]
     pub vault: Account<'info, Vault>,
@@ -1420,7 +6482,7 @@ pub struct ReconcilePremiumBalance<'info> {
     #[account(
         address = vault.premium_token_account
     )]
-    pub vault_premium_account: Account<'info, TokenAccount>,
+    pub vault_premium_account: InterfaceAccount<'info, TokenAccount>,
 
     pub authority: Signer<'info>,
 }
@@ -1431,6 +6493,7 @@ pub struct CloseVault<'info> {
         mut,
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
         has_one = authority,
         close = authority
     )]
@@ -1440,6 +6503,122 @@ pub struct CloseVault<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(asset_id: String)]
+pub struct MigrateVault<'info> {
+    /// CHECK: Legacy layout; owner, discriminator and authority checked in the handler
+    #[account(
+        mut,
+        seeds = [b"vault", asset_id.as_bytes()],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    pub underlying_mint: InterfaceAccount<'info, Mint>,
+
+    pub premium_mint: InterfaceAccount<'info, Mint>,
+
+    /// Share mint of the legacy vault (checked against its layout)
+    pub legacy_share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = legacy_share_mint.decimals,
+        mint::authority = vault,
+        mint::token_program = token_program,
+        extensions::transfer_hook::authority = vault,
+        extensions::transfer_hook::program_id = crate::ID,
+        seeds = [b"share_mint_v1", vault.key().as_ref()],
+        bump
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Transfer hook ExtraAccountMetaList of the share mint, written
+    /// by the handler
+    #[account(
+        init,
+        payer = authority,
+        space = ExtraAccountMetaList::size_of(SHARE_HOOK_EXTRA_ACCOUNTS)?,
+        seeds = [b"extra-account-metas", share_mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    /// Legacy whitelist, closed once its makers are queued as AddMarketMaker
+    /// actions (PendingAction PDAs passed in list order as remaining accounts)
+    #[account(
+        mut,
+        seeds = [b"whitelist", vault.key().as_ref()],
+        bump = whitelist.bump,
+        has_one = vault,
+        close = authority
+    )]
+    pub whitelist: Option<Account<'info, VaultWhitelist>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    /// Token program of the new share mint (Token-2022, for the transfer hook)
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ConvertLegacyShares<'info> {
+    #[account(
+        seeds = [b"vault", vault.asset_id.as_bytes()],
+        bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
+        constraint = vault.legacy_share_mint != Pubkey::default() @ VaultError::NoLegacyShares
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        address = vault.legacy_share_mint,
+        mint::token_program = legacy_token_program
+    )]
+    pub legacy_share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = legacy_share_mint,
+        token::authority = user
+    )]
+    pub user_legacy_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = vault.share_mint
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = vault.share_mint
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + PremiumCheckpoint::INIT_SPACE,
+        seeds = [b"premium_checkpoint", vault.key().as_ref(), user_share_account.key().as_ref()],
+        bump
+    )]
+    pub premium_checkpoint: Account<'info, PremiumCheckpoint>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    /// Token program of the share mint (Token-2022, for the transfer hook)
+    pub token_program: Program<'info, Token2022>,
+    /// Token program of the legacy share mint
+    pub legacy_token_program: Interface<'info, TokenInterface>,
+}
+
 /// Force close context - uses UncheckedAccount to bypass deserialization
 /// SECURITY: Only the program upgrade authority should call this
 #[derive(Accounts)]
@@ -1465,13 +6644,13 @@ pub struct CloseOrphanedTokenAccount<'info> {
 
     /// The token account to close (share escrow, vault token account, etc.)
     #[account(mut)]
-    pub token_account: Account<'info, TokenAccount>,
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// Authority receiving the lamports
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
@@ -1479,6 +6658,7 @@ pub struct CreateShareMetadata<'info> {
     #[account(
         seeds = [b"vault", vault.asset_id.as_bytes()],
         bump = vault.bump,
+        constraint = vault.version == CURRENT_VAULT_VERSION @ VaultError::UnsupportedVaultVersion,
         has_one = authority,
         has_one = share_mint
     )]
@@ -1505,73 +6685,204 @@ pub struct CreateShareMetadata<'info> {
     pub token_metadata_program: AccountInfo<'info>,
 }
 
-// ============================================================================
-// Events
-// ============================================================================
-
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct DepositEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub shares_minted: u64,
+    pub fee_shares: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct WithdrawalRequestedEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct WithdrawalCancelledEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct WithdrawalProcessedEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+    pub fee_shares: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct EpochAdvancedEvent {
+    pub vault: Pubkey,
+    pub new_epoch: u64,
+    pub premium_earned: u64,
+    pub notional_exposed: u64,
+    pub avg_premium_bps: u32,
+    pub total_assets: u64,
+    pub total_shares: u64,
+    pub premium_balance_usdc: u64,
+    pub management_fee_shares: u64,
+    pub performance_fee: u64,
+    pub high_water_mark: u64,
+    pub premium_compounded: u64,
+    pub withdrawal_shares: u64,
+    pub withdrawal_amount: u64,
+    pub deposits_admitted: u64,
+    pub deposit_shares: u64,
+}
+
+#[event]
+pub struct DepositRequestedEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct DepositClaimedEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub shares_minted: u64,
+    pub fee_shares: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct DepositQueueUpdatedEvent {
+    pub vault: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct CompoundConfigUpdatedEvent {
+    pub vault: Pubkey,
+    pub swap_program: Pubkey,
+    pub compound_bps: u16,
+}
+
+#[event]
+pub struct PremiumCompoundedEvent {
+    pub vault: Pubkey,
+    pub premium_spent: u64,
+    pub underlying_received: u64,
+    pub total_assets: u64,
+    pub price_per_share: u64,
+}
+
+#[event]
+pub struct FeeConfigUpdatedEvent {
+    pub vault: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub management_fee_bps: u16,
+    pub performance_fee_bps: u16,
+    pub deposit_fee_bps: u16,
+    pub withdrawal_fee_bps: u16,
+}
+
+#[event]
+pub struct NotionalExposureEvent {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    pub position: Pubkey,
+    pub market_maker: Pubkey,
+    pub notional_tokens: u64,
+    pub premium: u64,
+    pub strike_price: u64,
+    pub total_notional_this_epoch: u64,
+    pub total_premium_this_epoch: u64,
+    pub avg_premium_bps: u32,
+}
+
+#[event]
+pub struct SignedQuoteFilledEvent {
+    pub vault: Pubkey,
+    pub market_maker: Pubkey,
+    pub rfq_id: u64,
+    pub position: Pubkey,
+    pub premium: u64,
+}
+
 #[event]
-pub struct DepositEvent {
+pub struct PremiumCollectedEvent {
     pub vault: Pubkey,
-    pub user: Pubkey,
+    pub payer: Pubkey,
     pub amount: u64,
-    pub shares_minted: u64,
     pub epoch: u64,
 }
 
 #[event]
-pub struct WithdrawalRequestedEvent {
+pub struct SettlementPaidEvent {
     pub vault: Pubkey,
-    pub user: Pubkey,
-    pub shares: u64,
+    pub position: Pubkey,
+    pub recipient: Pubkey,
+    /// Collateral paid (underlying for calls, premium_mint for puts)
+    pub amount: u64,
+    /// Intrinsic value in premium_mint tokens
+    pub value: u64,
     pub epoch: u64,
 }
 
+/// Emitted when an ITM settlement reduces the vault's assets
 #[event]
-pub struct WithdrawalProcessedEvent {
+pub struct SettlementLossEvent {
     pub vault: Pubkey,
-    pub user: Pubkey,
-    pub shares: u64,
-    pub amount: u64,
     pub epoch: u64,
+    pub collateral_paid: u64,
+    pub total_assets: u64,
+    pub price_per_share_before: u64,
+    pub price_per_share_after: u64,
 }
 
 #[event]
-pub struct EpochAdvancedEvent {
+pub struct OracleConfigUpdatedEvent {
     pub vault: Pubkey,
-    pub new_epoch: u64,
-    pub premium_earned: u64,
-    pub notional_exposed: u64,
-    pub avg_premium_bps: u32,
-    pub total_assets: u64,
-    pub total_shares: u64,
-    pub premium_balance_usdc: u64,
+    pub price_feed_id: [u8; 32],
+    pub max_price_staleness: i64,
+    pub max_confidence_bps: u16,
 }
 
 #[event]
-pub struct NotionalExposureEvent {
+pub struct EpochSettledEvent {
     pub vault: Pubkey,
     pub epoch: u64,
-    pub notional_tokens: u64,
-    pub premium: u64,
-    pub total_notional_this_epoch: u64,
-    pub total_premium_this_epoch: u64,
-    pub avg_premium_bps: u32,
+    pub settlement_price: u64,
+    pub publish_time: i64,
+    pub positions: u32,
 }
 
 #[event]
-pub struct PremiumCollectedEvent {
+pub struct PositionExercisedEvent {
     pub vault: Pubkey,
-    pub payer: Pubkey,
-    pub amount: u64,
+    pub position: Pubkey,
+    pub market_maker: Pubkey,
     pub epoch: u64,
+    pub option_type: OptionType,
+    pub underlying_amount: u64,
+    pub strike_amount: u64,
+    pub total_assets: u64,
+    pub premium_balance_usdc: u64,
 }
 
 #[event]
-pub struct SettlementPaidEvent {
+pub struct SettlementModeUpdatedEvent {
     pub vault: Pubkey,
-    pub recipient: Pubkey,
-    pub amount: u64,
-    pub epoch: u64,
+    pub mode: SettlementMode,
 }
 
 #[event]
@@ -1582,18 +6893,51 @@ pub struct VaultPausedEvent {
 }
 
 #[event]
-pub struct ParamChangeQueuedEvent {
+pub struct ParamChangeExecutedEvent {
     pub vault: Pubkey,
-    pub new_min_epoch_duration: Option<i64>,
-    pub new_utilization_cap_bps: Option<u16>,
-    pub unlock_time: i64,
+    pub new_min_epoch_duration: i64,
+    pub new_utilization_cap_bps: u16,
+    pub new_max_total_assets: u64,
+    pub new_max_deposit_per_user: u64,
 }
 
+/// Indexers rescale historical UI prices and strikes by
+/// new_multiplier / old_multiplier from this event
 #[event]
-pub struct ParamChangeExecutedEvent {
+pub struct CorporateActionAppliedEvent {
     pub vault: Pubkey,
-    pub new_min_epoch_duration: i64,
-    pub new_utilization_cap_bps: u16,
+    pub epoch: u64,
+    pub old_multiplier: u64,
+    pub new_multiplier: u64,
+    pub open_positions: u32,
+}
+
+#[event]
+pub struct MakerCollateralUpdatedEvent {
+    pub vault: Pubkey,
+    pub market_maker: Pubkey,
+    pub deposited: u64,
+    pub withdrawn: u64,
+    pub collateral: u64,
+}
+
+#[event]
+pub struct MakerSlashedEvent {
+    pub vault: Pubkey,
+    pub market_maker: Pubkey,
+    /// Collateral taken from the maker
+    pub slashed: u64,
+    /// Amount received by the premium account (net of transfer fees)
+    pub received: u64,
+    pub unpaid_premium: u64,
+    pub collateral: u64,
+}
+
+#[event]
+pub struct MarketMakerAddedEvent {
+    pub vault: Pubkey,
+    pub market_maker: Pubkey,
+    pub notional_limit: u64,
 }
 
 /// SECURITY FIX M-4: Event emitted when a market maker is removed
@@ -1603,6 +6947,105 @@ pub struct MarketMakerRemovedEvent {
     pub market_maker: Pubkey,
 }
 
+#[event]
+pub struct PremiumClaimedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub share_account: Pubkey,
+    pub amount: u64,
+    pub premium_index: u128,
+}
+
+/// Event emitted for each recorded dividend distribution
+#[event]
+pub struct DividendRecordedEvent {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub treatment: DividendTreatment,
+    pub total_assets: u64,
+    pub premium_per_share_index: u128,
+}
+
+#[event]
+pub struct ActionQueuedEvent {
+    pub vault: Pubkey,
+    pub action_id: u64,
+    pub action: AdminAction,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct ActionExecutedEvent {
+    pub vault: Pubkey,
+    pub action_id: u64,
+    pub action: AdminAction,
+}
+
+#[event]
+pub struct ActionCancelledEvent {
+    pub vault: Pubkey,
+    pub action_id: u64,
+}
+
+#[event]
+pub struct AttestationIssuedEvent {
+    pub vault: Pubkey,
+    pub wallet: Pubkey,
+    pub attester: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct AttestationRevokedEvent {
+    pub vault: Pubkey,
+    pub wallet: Pubkey,
+}
+
+#[event]
+pub struct AuthorityProposedEvent {
+    pub vault: Pubkey,
+    pub current_authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub unlock_time: i64,
+}
+
+#[event]
+pub struct AuthorityTransferredEvent {
+    pub vault: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+/// Event emitted when vault roles are reassigned
+#[event]
+pub struct RolesUpdatedEvent {
+    pub vault: Pubkey,
+    pub keeper: Pubkey,
+    pub guardian: Pubkey,
+    pub settlement_operator: Pubkey,
+}
+
+#[event]
+pub struct VaultMigratedEvent {
+    pub vault: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub old_len: u32,
+    pub new_len: u32,
+}
+
+/// Event emitted when legacy shares are converted to hooked shares
+#[event]
+pub struct LegacySharesConvertedEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    /// Premium credited to the new shares' checkpoint for the legacy shares
+    pub premium_accrued: u64,
+}
+
 /// Event emitted when premium balance is reconciled
 #[event]
 pub struct PremiumBalanceReconciledEvent {
@@ -1661,7 +7104,7 @@ pub enum VaultError {
     DivisionByZero,
     #[msg("Timelock has not expired yet")]
     TimelockNotExpired,
-    #[msg("Direct param changes deprecated - use queue_param_change")]
+    #[msg("Direct param changes deprecated - use queue_action")]
     UseTimelockForParamChange,
     #[msg("Invalid parameter value")]
     InvalidParameter,
@@ -1673,4 +7116,979 @@ pub enum VaultError {
     InvalidTokenAccountOwner,
     #[msg("Duplicate account detected - accounts must be unique")]
     DuplicateAccount,
+    #[msg("Oracle price feed not configured for this vault")]
+    OracleNotConfigured,
+    #[msg("Invalid oracle price account")]
+    InvalidOracleAccount,
+    #[msg("Oracle price feed does not match vault configuration")]
+    OracleFeedMismatch,
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    #[msg("Oracle price must be positive")]
+    InvalidOraclePrice,
+    #[msg("Oracle confidence interval too wide")]
+    OracleConfidenceTooWide,
+    #[msg("Epoch already settled")]
+    EpochAlreadySettled,
+    #[msg("Cannot change oracle while exposure is outstanding")]
+    ExposureOutstanding,
+    #[msg("Option positions must be settled first")]
+    OpenPositions,
+    #[msg("Recipient is not the position counterparty")]
+    InvalidCounterparty,
+    #[msg("Missing Ed25519 quote signature instruction")]
+    MissingQuoteSignature,
+    #[msg("Invalid Ed25519 quote signature instruction")]
+    InvalidQuoteSignature,
+    #[msg("Quote terms do not match")]
+    QuoteMismatch,
+    #[msg("Quote deadline has passed")]
+    QuoteExpired,
+    #[msg("Instruction not allowed in this settlement mode")]
+    InvalidSettlementMode,
+    #[msg("Position is not in the money")]
+    PositionNotInTheMoney,
+    #[msg("Exercise window is still open")]
+    ExerciseWindowOpen,
+    #[msg("Exercise window has closed")]
+    ExerciseWindowClosed,
+    #[msg("Fee exceeds maximum")]
+    FeeTooHigh,
+    #[msg("Fee recipient account required")]
+    FeeAccountMissing,
+    #[msg("Deposits are queued; use request_deposit")]
+    DepositQueueEnabled,
+    #[msg("Deposit queue is not enabled")]
+    DepositQueueDisabled,
+    #[msg("Claim the previous deposit request first")]
+    DepositClaimPending,
+    #[msg("Withdrawal has already exited at the epoch roll")]
+    WithdrawalAlreadyExited,
+    #[msg("Not supported for this vault strategy")]
+    InvalidStrategy,
+    #[msg("Invalid compounding configuration")]
+    InvalidCompoundConfig,
+    #[msg("Amount exceeds premium set aside for compounding")]
+    InsufficientPremiumToCompound,
+    #[msg("Swap moved balances the wrong way")]
+    InvalidSwapResult,
+    #[msg("Mint has an unsupported Token-2022 extension")]
+    UnsupportedMintExtension,
+    #[msg("Dividend treatment not supported for this account and strategy")]
+    InvalidDividendTreatment,
+    #[msg("Dividend exceeds the vault's unaccounted token balance")]
+    DividendExceedsUnaccountedBalance,
+    #[msg("Caller does not hold the required vault role")]
+    MissingRole,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Admin change must be queued with queue_action")]
+    UseGovernanceQueue,
+    #[msg("Market maker accounts required for this action")]
+    MarketMakerAccountMissing,
+    #[msg("Fill exceeds the market maker's notional limit")]
+    ExceedsMarketMakerLimit,
+    #[msg("Market maker collateral does not cover unpaid premium")]
+    InsufficientMakerCollateral,
+    #[msg("Premium grace window has not elapsed")]
+    PremiumGraceWindowOpen,
+    #[msg("Deposit would exceed the vault's max_total_assets")]
+    ExceedsTvlCap,
+    #[msg("Deposit would exceed the per-wallet deposit cap")]
+    ExceedsUserDepositCap,
+    #[msg("Vault requires an attestation from its attester for this wallet")]
+    AttestationRequired,
+    #[msg("Attestation has expired")]
+    AttestationExpired,
+    #[msg("Account is not a vault with a known layout")]
+    InvalidVaultLayout,
+    #[msg("Vault already uses a versioned layout")]
+    VaultAlreadyMigrated,
+    #[msg("Vault layout version is not supported by this program")]
+    UnsupportedVaultVersion,
+    #[msg("Signer is not the vault authority")]
+    Unauthorized,
+    #[msg("Process pending withdrawals before migrating")]
+    PendingWithdrawals,
+    #[msg("Epoch settlement account required for an epoch with exposure")]
+    EpochSettlementMissing,
+    #[msg("Transfer hook invoked outside a share transfer")]
+    NotTransferring,
+    #[msg("Premium checkpoint of the fee share account required")]
+    PremiumCheckpointMissing,
+    #[msg("Vault multiplier does not match the underlying mint's scaled UI amount")]
+    MultiplierMismatch,
+    #[msg("Corporate action pending; execute or cancel it first")]
+    CorporateActionPending,
+    #[msg("Underlying mint account required for this action")]
+    UnderlyingMintMissing,
+    #[msg("Pending action accounts must be the PDAs of the actions queued")]
+    InvalidPendingActionAccount,
+    #[msg("Vault has no legacy share mint to convert from")]
+    NoLegacyShares,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCALE: u64 = PRICE_PER_SHARE_SCALE as u64;
+
+    /// Covered-call vault with 6-decimal underlying and no virtual offset
+    fn covered_call_vault(total_assets: u64, total_shares: u64) -> Vault {
+        Vault {
+            total_assets,
+            total_shares,
+            underlying_decimals: 6,
+            ..Vault::default()
+        }
+    }
+
+    #[test]
+    fn value_per_share_counts_premium_paid_out() {
+        // 1 underlying token per share at 100 USDC
+        let price = 100_000_000;
+        let mut vault = covered_call_vault(1_000_000, 1_000_000);
+        let high_water_mark = vault.value_per_share(price).unwrap();
+        assert_eq!(high_water_mark, 100 * SCALE);
+
+        // ITM call pays out 10% of the underlying
+        vault.total_assets = 900_000;
+        assert!(vault.value_per_share(price).unwrap() < high_water_mark);
+
+        // Premium paid out (not compounded) recovers the loss
+        vault.credit_premium(10_000_000).unwrap();
+        assert_eq!(vault.value_per_share(price).unwrap(), high_water_mark);
+    }
+
+    #[test]
+    fn value_per_share_includes_premium_awaiting_compounding() {
+        let price = 100_000_000;
+        let mut vault = covered_call_vault(1_000_000, 1_000_000);
+        vault.premium_to_compound = 5_000_000;
+        assert_eq!(vault.value_per_share(price).unwrap(), 105 * SCALE);
+    }
+
+    fn checkpoint(shares: u64, premium_index: u128) -> PremiumCheckpoint {
+        PremiumCheckpoint {
+            vault: Pubkey::default(),
+            share_account: Pubkey::new_unique(),
+            premium_index,
+            shares,
+            accrued: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn transferred_shares_accrue_once() {
+        // 100 USDC credited over 100 shares, all held by X
+        let mut vault = covered_call_vault(100, 100);
+        let mut x = checkpoint(100, 0);
+        let mut y = checkpoint(0, 0);
+
+        // X syncs, transfers everything to Y, Y claims; then the index rolls
+        x.accrue(vault.premium_per_share_index, 100, 100).unwrap();
+        move_premium_accrual(vault.premium_per_share_index, &mut x, 0, &mut y, 100, 100).unwrap();
+        y.accrue(vault.premium_per_share_index, 100, 100).unwrap();
+        vault.credit_premium(100).unwrap();
+
+        // Both claim; Y claims again after X moves the shares back
+        x.accrue(vault.premium_per_share_index, 0, 0).unwrap();
+        y.accrue(vault.premium_per_share_index, 100, 100).unwrap();
+        move_premium_accrual(vault.premium_per_share_index, &mut y, 0, &mut x, 100, 100).unwrap();
+        x.accrue(vault.premium_per_share_index, 100, 100).unwrap();
+        assert_eq!(x.accrued, 0);
+        assert_eq!(y.accrued, 100);
+    }
+
+    #[test]
+    fn claim_then_transfer_after_roll_accrues_once() {
+        let mut vault = covered_call_vault(100, 100);
+        let mut x = checkpoint(100, 0);
+        let mut y = checkpoint(0, 0);
+        vault.credit_premium(100).unwrap();
+
+        // X claims the roll, then moves the shares to Y, who claims again
+        x.accrue(vault.premium_per_share_index, 100, 100).unwrap();
+        move_premium_accrual(vault.premium_per_share_index, &mut x, 0, &mut y, 100, 100).unwrap();
+        y.accrue(vault.premium_per_share_index, 100, 100).unwrap();
+        assert_eq!(x.accrued + y.accrued, 100);
+    }
+
+    #[test]
+    fn burned_shares_stop_accruing() {
+        // Shares burned outside the vault leave the checkpoint stale
+        let mut vault = covered_call_vault(100, 100);
+        let mut x = checkpoint(100, 0);
+        vault.credit_premium(100).unwrap();
+        x.accrue(vault.premium_per_share_index, 40, 40).unwrap();
+        assert_eq!(x.accrued, 40);
+    }
+
+    #[test]
+    fn covered_call_compounding_waits_for_swap() {
+        let price = 100_000_000;
+        let mut vault = covered_call_vault(1_000_000, 1_000_000);
+        vault.compound_bps = 5_000;
+
+        // Half the premium waits for compound_premium, half is paid out
+        let compounded = vault.book_epoch_premium(10_000_000).unwrap();
+        assert_eq!(compounded, 5_000_000);
+        assert_eq!(vault.premium_to_compound, 5_000_000);
+        assert_eq!(vault.premium_balance_usdc, 5_000_000);
+        assert_eq!(vault.premium_per_share_index, 5 * SCALE as u128);
+        assert_eq!(vault.value_per_share(price).unwrap(), 110 * SCALE);
+
+        // Swapping at the valuation price leaves value per share unchanged
+        vault.record_compounded(5_000_000, 50_000).unwrap();
+        assert_eq!(vault.premium_to_compound, 0);
+        assert_eq!(vault.total_assets, 1_050_000);
+        assert_eq!(vault.value_per_share(price).unwrap(), 110 * SCALE);
+
+        // Cannot spend more than was set aside
+        assert!(vault.record_compounded(1, 1).is_err());
+    }
+
+    #[test]
+    fn put_compounding_adds_to_collateral() {
+        let mut vault = Vault {
+            strategy: Strategy::CashSecuredPut,
+            compound_bps: 2_500,
+            ..covered_call_vault(1_000_000, 1_000_000)
+        };
+        let compounded = vault.book_epoch_premium(1_000_000).unwrap();
+        assert_eq!(compounded, 250_000);
+        assert_eq!(vault.total_assets, 1_250_000);
+        assert_eq!(vault.premium_to_compound, 0);
+        assert_eq!(vault.premium_balance_usdc, 750_000);
+    }
+
+    #[test]
+    fn premium_index_counts_virtual_offset() {
+        // The virtual offset dilutes the index like real shares
+        let mut vault = Vault {
+            virtual_offset: 100,
+            ..covered_call_vault(900, 900)
+        };
+        vault.credit_premium(1_000).unwrap();
+        assert_eq!(vault.premium_per_share_index, SCALE as u128);
+
+        let mut holder = checkpoint(900, 0);
+        holder
+            .accrue(vault.premium_per_share_index, 900, 900)
+            .unwrap();
+        assert_eq!(holder.accrued, 900);
+
+        let snapshot = EpochSnapshot {
+            vault: Pubkey::default(),
+            epoch: 1,
+            price_per_share: 2 * SCALE,
+            premium_index: SCALE as u128,
+            assigned_underlying_per_share: 0,
+            withdrawal_shares: 0,
+            deposits_admitted: 0,
+            deposit_shares: 0,
+            start_timestamp: 0,
+            end_timestamp: 0,
+            notional_exposed: 0,
+            premium_earned: 0,
+            avg_premium_bps: 0,
+            settlement_paid: 0,
+            settlement_price: 0,
+            bump: 0,
+        };
+        assert_eq!(snapshot.premium_for(900, 0).unwrap(), 900);
+        assert_eq!(snapshot.collateral_for(900).unwrap(), 1_800);
+    }
+
+    #[test]
+    fn premium_index_rounds_down() {
+        // 1 unit over 3 shares: holders never accrue more than was credited
+        let mut vault = covered_call_vault(3, 3);
+        vault.credit_premium(1).unwrap();
+        let mut holders = [checkpoint(1, 0), checkpoint(2, 0)];
+        for holder in holders.iter_mut() {
+            let shares = holder.shares;
+            holder
+                .accrue(vault.premium_per_share_index, shares, shares)
+                .unwrap();
+        }
+        assert!(holders.iter().map(|h| h.accrued).sum::<u64>() <= 1);
+    }
+
+    #[test]
+    fn exiting_shares_are_paid_premium_once() {
+        let mut vault = covered_call_vault(1_000, 1_000);
+        let mut holder = checkpoint(1_000, 0);
+        vault.credit_premium(1_000).unwrap();
+
+        // request_withdrawal: burn 400, checkpoint the premium so far, queue
+        holder
+            .accrue(vault.premium_per_share_index, 1_000, 600)
+            .unwrap();
+        let premium_index_at_request = vault.queue_withdrawal(400).unwrap();
+        assert_eq!(holder.accrued, 1_000);
+
+        // More premium before the roll; the burned shares still count
+        vault.credit_premium(500).unwrap();
+
+        // advance_epoch exits the shares with the premium since the request
+        let mut snapshot = EpochSnapshot::default();
+        vault.exit_queued_withdrawals(&mut snapshot).unwrap();
+        assert_eq!(vault.premium_withdrawal_reserve, 200);
+
+        // process_withdrawal and claim_premium pay each credit once
+        let payout = vault
+            .redeem_exited_withdrawal(&snapshot, 400, premium_index_at_request)
+            .unwrap();
+        holder
+            .accrue(vault.premium_per_share_index, 600, 600)
+            .unwrap();
+        assert_eq!(payout.premium, 200);
+        assert_eq!(payout.collateral, 400);
+        assert_eq!(holder.accrued, 1_300);
+        assert_eq!(vault.premium_balance_usdc, holder.accrued);
+        assert_eq!(vault.premium_withdrawal_reserve, 0);
+        assert_eq!(vault.pending_withdrawals, 0);
+    }
+
+    #[test]
+    fn requests_at_different_indexes_exit_together() {
+        let mut vault = covered_call_vault(1_000, 1_000);
+        let early = vault.queue_withdrawal(100).unwrap();
+        vault.credit_premium(1_000).unwrap();
+        let late = vault.queue_withdrawal(100).unwrap();
+        vault.credit_premium(1_000).unwrap();
+
+        let mut snapshot = EpochSnapshot::default();
+        vault.exit_queued_withdrawals(&mut snapshot).unwrap();
+        assert_eq!(snapshot.withdrawal_shares, 200);
+        assert_eq!(vault.premium_withdrawal_reserve, 300);
+
+        let early = vault
+            .redeem_exited_withdrawal(&snapshot, 100, early)
+            .unwrap();
+        let late = vault
+            .redeem_exited_withdrawal(&snapshot, 100, late)
+            .unwrap();
+        assert_eq!((early.premium, late.premium), (200, 100));
+        assert_eq!(vault.premium_withdrawal_reserve, 0);
+    }
+
+    #[test]
+    fn cancelled_withdrawal_keeps_premium_credited_while_burned() {
+        let mut vault = covered_call_vault(1_000, 1_000);
+        let mut holder = checkpoint(1_000, 0);
+
+        // request_withdrawal burns 400, then premium is credited
+        holder
+            .accrue(vault.premium_per_share_index, 1_000, 600)
+            .unwrap();
+        let premium_index_at_request = vault.queue_withdrawal(400).unwrap();
+        vault.credit_premium(1_000).unwrap();
+
+        // cancel_withdrawal re-mints the shares with the premium they earned
+        vault
+            .unqueue_withdrawal(400, premium_index_at_request)
+            .unwrap();
+        holder
+            .accrue(vault.premium_per_share_index, 600, 1_000)
+            .unwrap();
+        holder
+            .accrue_since(vault.premium_per_share_index, premium_index_at_request, 400)
+            .unwrap();
+        assert_eq!(holder.accrued, 1_000);
+
+        // Nothing exits at the roll
+        let mut snapshot = EpochSnapshot::default();
+        vault.exit_queued_withdrawals(&mut snapshot).unwrap();
+        assert_eq!(vault.premium_withdrawal_reserve, 0);
+        assert_eq!(vault.premium_balance_usdc, holder.accrued);
+        assert_eq!(vault.total_shares, 1_000);
+    }
+
+    fn settlement(settlement_price: u64) -> EpochSettlement {
+        EpochSettlement {
+            vault: Pubkey::default(),
+            epoch: 1,
+            position_count: 1,
+            total_premium: 0,
+            settlement_price,
+            publish_time: 0,
+            underlying_decimals: 6,
+            settled_at: 0,
+            total_paid: 0,
+            is_settled: true,
+            bump: 0,
+        }
+    }
+
+    /// 2 whole underlying tokens struck at 100 USDC
+    fn position(option_type: OptionType) -> OptionPosition {
+        OptionPosition {
+            vault: Pubkey::default(),
+            epoch: 1,
+            index: 0,
+            counterparty: Pubkey::default(),
+            option_type,
+            strike_price: 100_000_000,
+            expiry: 0,
+            notional: 2_000_000,
+            premium: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn call_settles_in_underlying() {
+        let call = position(OptionType::Call);
+        let itm = settlement(120_000_000);
+        assert_eq!(call.intrinsic_value(&itm).unwrap(), 40_000_000);
+        // 40 USDC of intrinsic value is 1/3 of a token at 120
+        assert_eq!(call.collateral_payout(&itm).unwrap(), 333_333);
+
+        let otm = settlement(100_000_000);
+        assert_eq!(call.intrinsic_value(&otm).unwrap(), 0);
+        assert_eq!(call.collateral_payout(&otm).unwrap(), 0);
+    }
+
+    #[test]
+    fn put_settles_in_premium_mint() {
+        let put = position(OptionType::Put);
+        let itm = settlement(80_000_000);
+        assert_eq!(put.intrinsic_value(&itm).unwrap(), 40_000_000);
+        assert_eq!(put.collateral_payout(&itm).unwrap(), 40_000_000);
+        assert_eq!(put.strike_value(&itm).unwrap(), 200_000_000);
+
+        let otm = settlement(120_000_000);
+        assert_eq!(put.collateral_payout(&otm).unwrap(), 0);
+    }
+
+    #[test]
+    fn raw_price_applies_multiplier() {
+        // After a 2:1 split one raw token is worth two UI tokens
+        let vault = Vault {
+            underlying_multiplier: 2 * SCALE,
+            ..Vault::default()
+        };
+        assert_eq!(vault.to_raw_price(50_000_000).unwrap(), 100_000_000);
+    }
+
+    /// Fully verified PriceUpdateV2 for feed [1; 32] at $100 (exponent -2)
+    fn price_update(publish_time: i64) -> AccountInfo<'static> {
+        let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[0; 32]); // write authority
+        data.push(1); // VerificationLevel::Full
+        data.extend_from_slice(&[1; 32]);
+        data.extend_from_slice(&10_000i64.to_le_bytes());
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&(-2i32).to_le_bytes());
+        data.extend_from_slice(&publish_time.to_le_bytes());
+        data.extend_from_slice(&[0; 32]); // prev publish time, EMA, posted slot
+        account_info(Pubkey::new_unique(), PYTH_RECEIVER_PROGRAM_ID, data)
+    }
+
+    #[test]
+    fn settlement_price_is_from_the_expiry_window() {
+        let vault = Vault {
+            price_feed_id: [1; 32],
+            max_price_staleness: 60,
+            max_confidence_bps: 100,
+            ..Vault::default()
+        };
+        let expiry = 1_700_000_000;
+        for (publish_time, accepted) in [
+            (expiry - 1, false),
+            (expiry, true),
+            (expiry + 60, true),
+            (expiry + 61, false),
+        ] {
+            let price = load_oracle_price(&vault, &price_update(publish_time), expiry);
+            assert_eq!(price.is_ok(), accepted, "published at {publish_time}");
+        }
+    }
+
+    fn legacy_vault() -> LegacyVaultV0 {
+        LegacyVaultV0 {
+            authority: Pubkey::new_unique(),
+            asset_id: "NVDAx".to_string(),
+            underlying_mint: Pubkey::new_unique(),
+            share_mint: Pubkey::new_unique(),
+            vault_token_account: Pubkey::new_unique(),
+            premium_mint: Pubkey::new_unique(),
+            premium_token_account: Pubkey::new_unique(),
+            share_escrow: Pubkey::new_unique(),
+            total_assets: 1_000_000,
+            total_shares: 1_000_000,
+            virtual_offset: 0,
+            epoch: 7,
+            utilization_cap_bps: 8_000,
+            min_epoch_duration: 604_800,
+            last_roll_timestamp: 1_700_000_000,
+            pending_withdrawals: 0,
+            epoch_notional_exposed: 0,
+            epoch_premium_earned: 0,
+            epoch_premium_per_token_bps: 0,
+            premium_balance_usdc: 2_000_000,
+            is_paused: false,
+            pending_min_epoch_duration: 0,
+            pending_utilization_cap: 0,
+            param_change_unlock_time: 0,
+            bump: 254,
+        }
+    }
+
+    #[test]
+    fn legacy_vault_migrates() {
+        let legacy = legacy_vault();
+        let authority = legacy.authority;
+        let mut data = Vec::new();
+        legacy.serialize(&mut data).unwrap();
+
+        let legacy = LegacyVaultV0::deserialize(&mut &data[..]).unwrap();
+        let legacy_share_mint = legacy.share_mint;
+        let share_mint = Pubkey::new_unique();
+        let vault = legacy
+            .into_vault(
+                share_mint,
+                6,
+                SCALE,
+                anchor_spl::token::ID,
+                anchor_spl::token::ID,
+            )
+            .unwrap();
+        assert_eq!(vault.version, CURRENT_VAULT_VERSION);
+        assert_eq!(vault.asset_id, "NVDAx");
+        assert_eq!(vault.epoch, 7);
+        assert_eq!(vault.total_assets, 1_000_000);
+        assert_eq!(vault.total_shares, 1_000_000);
+        assert_eq!(vault.underlying_decimals, 6);
+        assert_eq!(vault.underlying_multiplier, SCALE);
+        assert_eq!(vault.keeper, authority);
+        assert_eq!(vault.fee_recipient, authority);
+        assert_eq!(vault.bump, 254);
+
+        assert_eq!(vault.share_mint, share_mint);
+        assert_eq!(vault.legacy_share_mint, legacy_share_mint);
+
+        // Legacy premium is credited to the index
+        assert_eq!(vault.premium_balance_usdc, 2_000_000);
+        assert_eq!(vault.premium_per_share_index, 2 * SCALE as u128);
+
+        // Converted shares are paid it pro-rata, on top of later credits
+        let mut vault = vault;
+        vault.credit_premium(1_000_000).unwrap();
+        let mut holder = checkpoint(0, vault.premium_per_share_index);
+        holder
+            .accrue(vault.premium_per_share_index, 0, 250_000)
+            .unwrap();
+        holder
+            .accrue_since(vault.premium_per_share_index, 0, 250_000)
+            .unwrap();
+        assert_eq!(holder.accrued, 750_000);
+
+        // The migrated layout fills the new account
+        let mut account = Vec::new();
+        vault.try_serialize(&mut account).unwrap();
+        assert!(account.len() <= 8 + Vault::INIT_SPACE);
+    }
+
+    #[test]
+    fn stored_vault_version_reads_both_layouts() {
+        // Version 0 accounts were sized for a 64-byte asset_id
+        let legacy = LegacyVaultV0 {
+            asset_id: "A".repeat(64),
+            ..legacy_vault()
+        };
+        let mut account = Vault::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut account).unwrap();
+        assert_eq!(account.len(), VAULT_V0_LEN);
+        assert_eq!(stored_vault_version(&account).unwrap(), 0);
+
+        let vault = legacy
+            .into_vault(
+                Pubkey::new_unique(),
+                6,
+                SCALE,
+                anchor_spl::token::ID,
+                anchor_spl::token::ID,
+            )
+            .unwrap();
+        let mut account = Vec::new();
+        vault.try_serialize(&mut account).unwrap();
+        account.resize(8 + Vault::INIT_SPACE, 0);
+        assert_eq!(
+            stored_vault_version(&account).unwrap(),
+            CURRENT_VAULT_VERSION
+        );
+        account[VAULT_VERSION_OFFSET] = CURRENT_VAULT_VERSION + 1;
+        assert_eq!(
+            stored_vault_version(&account).unwrap(),
+            CURRENT_VAULT_VERSION + 1
+        );
+    }
+
+    #[test]
+    fn converted_shares_exit_with_legacy_premium_once() {
+        let mut vault = legacy_vault()
+            .into_vault(
+                Pubkey::new_unique(),
+                6,
+                SCALE,
+                anchor_spl::token::ID,
+                anchor_spl::token::ID,
+            )
+            .unwrap();
+
+        // convert_legacy_shares pays the legacy premium on 250_000 shares
+        let mut holder = checkpoint(0, vault.premium_per_share_index);
+        holder
+            .accrue(vault.premium_per_share_index, 0, 250_000)
+            .unwrap();
+        holder
+            .accrue_since(vault.premium_per_share_index, 0, 250_000)
+            .unwrap();
+
+        // All of them are withdrawn before the first roll credits more
+        holder
+            .accrue(vault.premium_per_share_index, 250_000, 0)
+            .unwrap();
+        let premium_index_at_request = vault.queue_withdrawal(250_000).unwrap();
+        vault.credit_premium(1_000_000).unwrap();
+
+        let mut snapshot = EpochSnapshot::default();
+        vault.exit_queued_withdrawals(&mut snapshot).unwrap();
+        let payout = vault
+            .redeem_exited_withdrawal(&snapshot, 250_000, premium_index_at_request)
+            .unwrap();
+        assert_eq!(holder.accrued, 500_000);
+        assert_eq!(payout.premium, 250_000);
+        assert_eq!(vault.premium_balance_usdc, 2_750_000);
+    }
+
+    #[test]
+    fn unrolled_premium_is_tracked() {
+        // Premium received mid-epoch cannot be recorded as a dividend
+        let mut vault = covered_call_vault(1_000_000, 1_000_000);
+        vault.credit_premium(3_000_000).unwrap();
+        vault.record_premium_received(2_000_000).unwrap();
+        assert_eq!(vault.tracked_premium_balance().unwrap(), 5_000_000);
+        assert_eq!(vault.available_premium(5_000_000), 3_000_000);
+    }
+
+    #[test]
+    fn admin_actions_fit_pending_action() {
+        // Largest payload is SetRoles (three pubkeys)
+        let set_roles = AdminAction::SetRoles {
+            keeper: Pubkey::new_unique(),
+            guardian: Pubkey::new_unique(),
+            settlement_operator: Pubkey::new_unique(),
+        };
+        assert_eq!(
+            AdminAction::INIT_SPACE,
+            set_roles.try_to_vec().unwrap().len()
+        );
+
+        let oracle = AdminAction::SetOracleConfig {
+            price_feed_id: [7; 32],
+            max_price_staleness: 60,
+            max_confidence_bps: 100,
+        };
+        assert!(oracle.try_to_vec().unwrap().len() <= AdminAction::INIT_SPACE);
+    }
+
+    #[test]
+    fn admin_actions_validate_on_queue() {
+        assert!(AdminAction::CorporateAction { new_multiplier: 0 }
+            .validate()
+            .is_err());
+        assert!(AdminAction::TransferAuthority {
+            new_authority: Pubkey::default()
+        }
+        .validate()
+        .is_err());
+        assert!(AdminAction::SetCompoundConfig {
+            swap_program: Pubkey::new_unique(),
+            compound_bps: 10_001,
+        }
+        .validate()
+        .is_err());
+        assert!(AdminAction::SetOracleConfig {
+            price_feed_id: [0; 32],
+            max_price_staleness: 60,
+            max_confidence_bps: 0,
+        }
+        .validate()
+        .is_err());
+        assert!(AdminAction::SetDepositQueue { enabled: true }
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn queued_actions_wait_out_the_timelock() {
+        let vault_key = Pubkey::new_unique();
+        let mut vault = Vault {
+            timelock_delay: DEFAULT_TIMELOCK_DELAY,
+            next_action_id: 3,
+            ..Vault::default()
+        };
+        let add_maker = AdminAction::AddMarketMaker {
+            market_maker: Pubkey::new_unique(),
+            notional_limit: 1_000,
+        };
+        let pending = vault
+            .queue_action(vault_key, add_maker.clone(), 100, 255)
+            .unwrap();
+        assert_eq!(pending.id, 3);
+        assert!(pending.action == add_maker);
+        assert_eq!(pending.executable_at, 100 + DEFAULT_TIMELOCK_DELAY);
+        assert_eq!(vault.next_action_id, 4);
+
+        // Invalid actions and a second corporate action are not queued
+        let no_limit = AdminAction::AddMarketMaker {
+            market_maker: Pubkey::new_unique(),
+            notional_limit: 0,
+        };
+        assert!(vault.queue_action(vault_key, no_limit, 100, 255).is_err());
+        let split = AdminAction::CorporateAction {
+            new_multiplier: 2 * SCALE,
+        };
+        vault
+            .queue_action(vault_key, split.clone(), 100, 255)
+            .unwrap();
+        assert_eq!(vault.pending_underlying_multiplier, 2 * SCALE);
+        assert!(vault.queue_action(vault_key, split, 100, 255).is_err());
+        assert_eq!(vault.next_action_id, 5);
+    }
+
+    #[test]
+    fn market_maker_account_space_matches_layout() {
+        let maker = MarketMakerAccount {
+            vault: Pubkey::new_unique(),
+            market_maker: Pubkey::new_unique(),
+            status: MarketMakerStatus::Active,
+            notional_limit: 1,
+            outstanding_exposure: 0,
+            outstanding_notional_value: 0,
+            total_premium_paid: 0,
+            total_settlement_received: 0,
+            collateral: 0,
+            unpaid_premium: 0,
+            premium_due_at: 0,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        maker.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + MarketMakerAccount::INIT_SPACE);
+    }
+
+    #[test]
+    fn maker_collateral_covers_notional_and_unpaid_premium() {
+        let mut maker = MarketMakerAccount {
+            vault: Pubkey::default(),
+            market_maker: Pubkey::new_unique(),
+            status: MarketMakerStatus::Active,
+            notional_limit: u64::MAX,
+            outstanding_exposure: 0,
+            // 2 tokens struck at 100 USDC
+            outstanding_notional_value: notional_value(2_000_000, 100_000_000, 6).unwrap(),
+            total_premium_paid: 0,
+            total_settlement_received: 0,
+            collateral: 20_000_000,
+            unpaid_premium: 0,
+            premium_due_at: 0,
+            bump: 0,
+        };
+        assert_eq!(maker.outstanding_notional_value, 200_000_000);
+        assert!(maker.check_collateral().is_ok());
+
+        maker.unpaid_premium = 1;
+        assert!(maker.check_collateral().is_err());
+
+        // Closing the position releases its collateral requirement
+        maker.close_position(0, 200_000_000, 0).unwrap();
+        assert_eq!(maker.required_collateral().unwrap(), 1);
+    }
+
+    #[test]
+    fn deposit_caps_tighten_independently() {
+        // Lower only the TVL cap while the per-wallet cap stays uncapped
+        assert_eq!(tightened_cap(0, 0).unwrap(), 0);
+        assert_eq!(tightened_cap(1_000, 500).unwrap(), 500);
+        assert_eq!(tightened_cap(0, 500).unwrap(), 500);
+        assert_eq!(tightened_cap(1_000, 0).unwrap(), 1_000);
+        assert!(tightened_cap(500, 1_000).is_err());
+    }
+
+    #[test]
+    fn share_hook_metas_fit_their_list() {
+        let metas = share_hook_extra_metas(&Pubkey::new_unique()).unwrap();
+        assert_eq!(metas.len(), SHARE_HOOK_EXTRA_ACCOUNTS);
+        let mut data = vec![0; ExtraAccountMetaList::size_of(SHARE_HOOK_EXTRA_ACCOUNTS).unwrap()];
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas).unwrap();
+    }
+
+    #[test]
+    fn attestation_gates_only_gated_vaults() {
+        let attester = Pubkey::new_unique();
+        let attestation = Attestation {
+            vault: Pubkey::default(),
+            wallet: Pubkey::new_unique(),
+            attester,
+            expires_at: 100,
+            bump: 0,
+        };
+        let mut vault = Vault::default();
+        assert!(vault.check_attestation(None, 0).is_ok());
+
+        vault.attester = attester;
+        assert!(vault.check_attestation(None, 0).is_err());
+        assert!(vault.check_attestation(Some(&attestation), 99).is_ok());
+        assert!(vault.check_attestation(Some(&attestation), 100).is_err());
+
+        // Rotating the attester invalidates existing attestations
+        vault.attester = Pubkey::new_unique();
+        assert!(vault.check_attestation(Some(&attestation), 0).is_err());
+    }
+
+    // ------------------------------------------------------------------------
+    // Instruction tests
+    // Handlers run through the program entrypoint against in-memory accounts.
+    // Only handlers without CPIs can run: invoke needs the SBF runtime
+    // ------------------------------------------------------------------------
+
+    use anchor_lang::solana_program::entrypoint::ProgramResult;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use anchor_spl::token_2022::spl_token_2022;
+
+    fn account_info(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            false,
+            Box::leak(Box::new(1_000_000_000)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )
+    }
+
+    /// Vault account at its PDA, as initialize_vault stores it
+    fn vault_account(vault: Vault) -> AccountInfo<'static> {
+        let asset_id = "TEST".to_string();
+        let (key, bump) =
+            Pubkey::find_program_address(&[b"vault", asset_id.as_bytes()], &crate::ID);
+        let vault = Vault {
+            asset_id,
+            bump,
+            version: CURRENT_VAULT_VERSION,
+            ..vault
+        };
+        let mut data = Vec::with_capacity(8 + Vault::INIT_SPACE);
+        vault.try_serialize(&mut data).unwrap();
+        account_info(key, crate::ID, data)
+    }
+
+    fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> AccountInfo<'static> {
+        let mut data = vec![0; spl_token_2022::state::Account::LEN];
+        spl_token_2022::state::Account {
+            mint,
+            owner,
+            amount,
+            state: spl_token_2022::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        account_info(Pubkey::new_unique(), spl_token_2022::ID, data)
+    }
+
+    /// Run an instruction on infos; accounts not in infos are empty wallets
+    fn process(
+        infos: &[&AccountInfo<'static>],
+        accounts: impl ToAccountMetas,
+        args: impl InstructionData,
+    ) -> ProgramResult {
+        let infos = accounts
+            .to_account_metas(None)
+            .into_iter()
+            .map(|meta| {
+                let mut info = infos
+                    .iter()
+                    .find(|info| *info.key == meta.pubkey)
+                    .map(|info| (*info).clone())
+                    .unwrap_or_else(|| account_info(meta.pubkey, Pubkey::default(), vec![]));
+                info.is_signer = meta.is_signer;
+                info.is_writable = meta.is_writable;
+                info
+            })
+            .collect::<Vec<_>>();
+        crate::entry(
+            &crate::ID,
+            Box::leak(infos.into_boxed_slice()),
+            &args.data(),
+        )
+    }
+
+    fn load<T: AccountDeserialize>(info: &AccountInfo) -> T {
+        T::try_deserialize(&mut &info.data.borrow()[..]).unwrap()
+    }
+
+    #[test]
+    fn reconcile_leaves_uncredited_premium_for_the_roll() {
+        // 1_500 held: 1_000 credited, 200 reserved for exits, 300 this epoch
+        let premium_account = token_account(Pubkey::new_unique(), Pubkey::new_unique(), 1_500);
+        let authority = Pubkey::new_unique();
+        let vault = vault_account(Vault {
+            authority,
+            premium_token_account: *premium_account.key,
+            premium_balance_usdc: 900,
+            premium_withdrawal_reserve: 200,
+            epoch_premium_received: 300,
+            ..covered_call_vault(1_000, 1_000)
+        });
+
+        process(
+            &[&vault, &premium_account],
+            crate::accounts::ReconcilePremiumBalance {
+                vault: *vault.key,
+                vault_premium_account: *premium_account.key,
+                authority,
+            },
+            crate::instruction::ReconcilePremiumBalance {},
+        )
+        .unwrap();
+        let vault: Vault = load(&vault);
+        assert_eq!(vault.premium_balance_usdc, 1_000);
+        assert_eq!(vault.epoch_premium_received, 300);
+    }
+
+    #[test]
+    fn vault_with_another_layout_version_is_rejected() {
+        let premium_account = token_account(Pubkey::new_unique(), Pubkey::new_unique(), 0);
+        let authority = Pubkey::new_unique();
+        let vault = vault_account(Vault {
+            authority,
+            premium_token_account: *premium_account.key,
+            ..covered_call_vault(1_000, 1_000)
+        });
+        vault.data.borrow_mut()[VAULT_VERSION_OFFSET] = CURRENT_VAULT_VERSION + 1;
+
+        let result = process(
+            &[&vault, &premium_account],
+            crate::accounts::ReconcilePremiumBalance {
+                vault: *vault.key,
+                vault_premium_account: *premium_account.key,
+                authority,
+            },
+            crate::instruction::ReconcilePremiumBalance {},
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            anchor_lang::error::Error::from(VaultError::UnsupportedVaultVersion).into()
+        );
+    }
 }